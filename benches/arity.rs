@@ -0,0 +1,164 @@
+//! Verifier cost of WHIR against the arity of the Merkle trees.
+//!
+//! Higher-arity trees have fewer path levels but more siblings per level. For every
+//! configuration, the proof size and the number of Keccak permutations spent by the verifier
+//! on Merkle hashing are printed once, then the verification time is benchmarked:
+//!
+//! ```text
+//! cargo bench --bench arity
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::extension::BinomialExtensionField;
+use p3_keccak::{Keccak256Hash, KeccakF};
+use p3_koala_bear::KoalaBear;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{
+    CompressionFunctionFromHasher, CryptographicPermutation, PaddingFreeSponge, Permutation,
+    SerializingHasher,
+};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use whir_p3::{
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        config::{WhirConfigExt, domain_separator},
+        proof::WhirProof,
+        prover::Prover,
+        verifier::Verifier,
+    },
+};
+
+type F = KoalaBear;
+type EF = BinomialExtensionField<F, 4>;
+
+type U64Hash = PaddingFreeSponge<CountingKeccakF, 25, 17, 4>;
+type FieldHash = SerializingHasher<U64Hash>;
+type Compress<const ARITY: usize> = CompressionFunctionFromHasher<U64Hash, ARITY, 4>;
+type MyChallenger = SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>>;
+type MyMmcs<const ARITY: usize> = MerkleTreeMmcs<F, u64, FieldHash, Compress<ARITY>, ARITY, 4>;
+
+/// `(num_variables, folding_factor)` of the benchmarked configurations.
+const SHAPES: [(usize, usize); 2] = [(16, 4), (20, 4)];
+
+/// Number of Keccak permutations run by the Merkle trees so far.
+static PERMUTATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Keccak-f permutation counting its calls in [`PERMUTATIONS`].
+#[derive(Clone, Copy, Debug, Default)]
+struct CountingKeccakF;
+
+impl Permutation<[u64; 25]> for CountingKeccakF {
+    fn permute_mut(&self, state: &mut [u64; 25]) {
+        PERMUTATIONS.fetch_add(1, Ordering::Relaxed);
+        KeccakF.permute_mut(state);
+    }
+}
+
+impl CryptographicPermutation<[u64; 25]> for CountingKeccakF {}
+
+fn new_challenger() -> MyChallenger {
+    MyChallenger::new(HashChallenger::new(vec![], Keccak256Hash))
+}
+
+/// Proves one configuration with `ARITY`-ary trees, reports its costs and benchmarks its
+/// verification.
+fn bench_arity<const ARITY: usize>(c: &mut Criterion, num_variables: usize, folding_factor: usize) {
+    let u64_hash = U64Hash::new(CountingKeccakF);
+    let mmcs = MyMmcs::<ARITY>::new(FieldHash::new(u64_hash), Compress::new(u64_hash), 0);
+
+    // No grinding, so that the benchmark measures the verifier checks only.
+    let whir_params = ProtocolParameters {
+        security_level: 100,
+        pow_bits: 0,
+        folding_factor: FoldingFactor::Constant(folding_factor),
+        mmcs,
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+        rs_domain_initial_reduction_factor: 1,
+    };
+    let params =
+        WhirConfig::<EF, F, MyMmcs<ARITY>, MyChallenger>::new(num_variables, whir_params.clone());
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let polynomial = Poly::<F>::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut initial_statement = params.initial_statement(polynomial, SumcheckStrategy::Svo);
+    let _ = initial_statement.evaluate(&Point::rand(&mut rng, num_variables));
+    let statement = initial_statement.normalize();
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 4>(&params);
+    domainsep.add_whir_proof::<_, _, 4>(&params);
+
+    let dft = Radix2DFTSmallBatch::<F>::new(1 << params.max_fft_size());
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &initial_statement.poly)
+        .unwrap();
+    let mut proof =
+        WhirProof::<F, EF, MyMmcs<ARITY>>::from_protocol_parameters(&whir_params, num_variables);
+    let mut prover_challenger = new_challenger();
+    domainsep.observe_domain_separator(&mut prover_challenger);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut proof,
+            &mut prover_challenger,
+            &mut initial_statement,
+            root,
+            &prover_data,
+        )
+        .unwrap();
+
+    let verify = || {
+        let mut challenger = new_challenger();
+        domainsep.observe_domain_separator(&mut challenger);
+        let parsed_commitment = CommitmentReader::new(&params)
+            .parse_commitment::<u64, 4>(&proof, &mut challenger)
+            .unwrap();
+        Verifier::new(&params)
+            .verify(
+                &proof,
+                &mut challenger,
+                &parsed_commitment,
+                statement.clone(),
+            )
+            .unwrap()
+    };
+
+    // The verifier computes the configuration fingerprint once, committing to a fixed
+    // matrix: that constant cost is not part of the openings.
+    let start = PERMUTATIONS.load(Ordering::Relaxed);
+    let _ = params.fingerprint();
+    let fingerprint_permutations = PERMUTATIONS.load(Ordering::Relaxed) - start;
+
+    let start = PERMUTATIONS.load(Ordering::Relaxed);
+    verify();
+    let verify_permutations =
+        PERMUTATIONS.load(Ordering::Relaxed) - start - fingerprint_permutations;
+
+    let proof_bytes = bincode::serialize(&proof).unwrap().len();
+    let id = format!("arity{ARITY}/nv{num_variables}_k{folding_factor}");
+    println!(
+        "{id}: proof {proof_bytes} bytes, {verify_permutations} Merkle permutations to verify"
+    );
+
+    c.bench_function(&format!("verify/{id}"), |b| b.iter(verify));
+}
+
+fn benchmark_arity(c: &mut Criterion) {
+    for (num_variables, folding_factor) in SHAPES {
+        bench_arity::<2>(c, num_variables, folding_factor);
+        bench_arity::<4>(c, num_variables, folding_factor);
+        bench_arity::<8>(c, num_variables, folding_factor);
+    }
+}
+
+criterion_group!(benches, benchmark_arity);
+criterion_main!(benches);
@@ -0,0 +1,217 @@
+//! Peak heap usage of the prover.
+//!
+//! A counting global allocator tracks the live heap size, and the peak reached while
+//! committing and while proving is reported for several configurations. Proving is measured
+//! twice: with the tree of the commitment borrowed, which keeps it alive for the whole proof,
+//! and with the tree handed over to `Prover::prove_owned`, which frees it after the first
+//! round. Run it on two revisions to compare their peak memory:
+//!
+//! ```text
+//! cargo bench --bench memory
+//! ```
+//!
+//! The per-round allocations behind these peaks are recorded as `bytes` fields of the
+//! `transpose & pad`, `dft` and `commit matrix` tracing spans of the prover.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use p3_challenger::DuplexChallenger;
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::{Field, extension::BinomialExtensionField};
+use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use whir_p3::{
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::writer::CommitmentWriter,
+        config::domain_separator,
+        proof::WhirProof,
+        prover::{Prover, pow::Grinder},
+    },
+};
+
+type F = KoalaBear;
+type EF = BinomialExtensionField<F, 4>;
+
+type Poseidon16 = Poseidon2KoalaBear<16>;
+type Poseidon24 = Poseidon2KoalaBear<24>;
+
+type MerkleHash = PaddingFreeSponge<Poseidon24, 24, 16, 8>; // leaf hashing
+type MerkleCompress = TruncatedPermutation<Poseidon16, 2, 8, 16>; // 2-to-1 compression
+type MyChallenger = DuplexChallenger<F, Poseidon16, 16, 8>;
+type PackedF = <F as Field>::Packing;
+type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MerkleHash, MerkleCompress, 2, 8>;
+
+/// `(num_variables, folding_factor)` of the measured configurations.
+const SHAPES: [(usize, usize); 3] = [(18, 4), (20, 4), (22, 4)];
+
+/// System allocator recording the current and peak live heap size.
+struct PeakAlloc {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl PeakAlloc {
+    fn add(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+    }
+
+    fn sub(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    /// Restarts peak tracking from the current heap size, which is returned.
+    fn reset_peak(&self) -> usize {
+        let current = self.current.load(Ordering::Relaxed);
+        self.peak.store(current, Ordering::Relaxed);
+        current
+    }
+
+    fn peak(&self) -> usize {
+        self.peak.load(Ordering::Relaxed)
+    }
+}
+
+// SAFETY: every call is forwarded to the system allocator, only sizes are recorded.
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            self.add(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        self.sub(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.add(new_size);
+            self.sub(layout.size());
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAlloc = PeakAlloc {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+};
+
+const fn mib(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+fn measure(num_variables: usize, folding_factor: usize) {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let poseidon16 = Poseidon16::new_from_rng_128(&mut rng);
+    let poseidon24 = Poseidon24::new_from_rng_128(&mut rng);
+    let mmcs = MyMmcs::new(
+        MerkleHash::new(poseidon24),
+        MerkleCompress::new(poseidon16.clone()),
+        0,
+    );
+
+    let whir_params = ProtocolParameters {
+        security_level: 100,
+        pow_bits: 0,
+        folding_factor: FoldingFactor::Constant(folding_factor),
+        mmcs,
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+        rs_domain_initial_reduction_factor: 1,
+    };
+    let params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+    let polynomial = Poly::<F>::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Svo);
+    let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    domainsep.add_whir_proof::<_, _, 8>(&params);
+    let mut challenger = MyChallenger::new(poseidon16);
+    domainsep.observe_domain_separator(&mut challenger);
+    let dft = Radix2DFTSmallBatch::<F>::new(1 << params.max_fft_size());
+
+    let commit = || {
+        CommitmentWriter::new(&params)
+            .commit(&dft, &statement.poly)
+            .unwrap()
+    };
+    let baseline = ALLOCATOR.reset_peak();
+    let (root, prover_data) = commit();
+    let commit_peak = ALLOCATOR.peak() - baseline;
+
+    // Both proofs start from the same transcript and statement.
+    let prove = |owned: bool, root, prover_data| {
+        let mut proof =
+            WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+        let mut challenger = challenger.clone();
+        let mut statement = statement.clone();
+
+        let baseline = ALLOCATOR.reset_peak();
+        let prover = Prover(&params);
+        let result = if owned {
+            prover.prove_owned(
+                &Grinder::default(),
+                &dft,
+                &mut proof,
+                &mut challenger,
+                &mut statement,
+                root,
+                prover_data,
+            )
+        } else {
+            prover.prove(
+                &dft,
+                &mut proof,
+                &mut challenger,
+                &mut statement,
+                root,
+                &prover_data,
+            )
+        };
+        result.unwrap();
+        ALLOCATOR.peak() - baseline
+    };
+    let borrowed_peak = prove(false, root, prover_data);
+    let (root, prover_data) = commit();
+    let owned_peak = prove(true, root, prover_data);
+
+    println!(
+        "nv{num_variables}_k{folding_factor}: commit peak {:.1} MiB; prove peak above the \
+         committed state {:.1} MiB with the tree borrowed, {:.1} MiB with the tree owned",
+        mib(commit_peak),
+        mib(borrowed_peak),
+        mib(owned_peak),
+    );
+}
+
+fn main() {
+    for (num_variables, folding_factor) in SHAPES {
+        measure(num_variables, folding_factor);
+    }
+}
@@ -1,9 +1,15 @@
 use core::hint::black_box;
 
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::DuplexChallenger;
-use p3_field::extension::BinomialExtensionField;
+use p3_commit::Mmcs;
+use p3_field::{Field, extension::BinomialExtensionField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_maybe_rayon::prelude::*;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
 use rand::{SeedableRng, rngs::SmallRng};
 use whir_p3::{
     fiat_shamir::domain_separator::DomainSeparator, whir::utils::get_challenge_stir_queries,
@@ -14,6 +20,11 @@ type EF = BinomialExtensionField<F, 4>;
 type Perm = Poseidon2BabyBear<16>;
 type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
 
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type PackedF = <F as Field>::Packing;
+type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
+
 fn setup_challenger() -> MyChallenger {
     let mut rng = SmallRng::seed_from_u64(42);
     let perm = Perm::new_from_rng_128(&mut rng);
@@ -124,5 +135,49 @@ fn bench_stir_queries(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_stir_queries);
+/// Opening and folding of the STIR queries of a first round, as done by the prover.
+///
+/// Compares the sequential loop with the parallel one used by the prover under the
+/// `parallel` feature.
+fn bench_stir_openings(c: &mut Criterion) {
+    // 2^22 evaluations committed with folding factor 5.
+    const FOLDING_FACTOR: usize = 5;
+    const LOG_HEIGHT: usize = 17;
+
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+    let matrix = RowMajorMatrix::<F>::rand(&mut rng, 1 << LOG_HEIGHT, 1 << FOLDING_FACTOR);
+    let (_, prover_data) = mmcs.commit_matrix(matrix);
+    let folding_randomness = Point::<EF>::rand(&mut rng, FOLDING_FACTOR);
+
+    let open_and_fold = |&index: &usize| {
+        let mut opening = mmcs.open_batch(index, &prover_data);
+        let values = opening.opened_values.swap_remove(0);
+        let eval = Poly::new(values.clone()).eval_base(&folding_randomness);
+        (values, opening.opening_proof, eval)
+    };
+
+    let mut group = c.benchmark_group("stir_openings");
+    // Query counts of the rounds of the main example.
+    for num_queries in [7, 26, 80, 256] {
+        let indexes: Vec<usize> = (0..num_queries)
+            .map(|i| (i * 0x9E37) % (1 << LOG_HEIGHT))
+            .collect();
+
+        group.bench_with_input(
+            BenchmarkId::new("sequential", num_queries),
+            &indexes,
+            |b, indexes| b.iter(|| indexes.iter().map(open_and_fold).collect::<Vec<_>>()),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("parallel", num_queries),
+            &indexes,
+            |b, indexes| b.iter(|| indexes.par_iter().map(open_and_fold).collect::<Vec<_>>()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_stir_queries, bench_stir_openings);
 criterion_main!(benches);
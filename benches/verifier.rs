@@ -0,0 +1,291 @@
+//! Verifier benchmarks, in full and broken down by phase.
+//!
+//! Every benchmark runs over the same grid of configurations (number of variables, folding
+//! factor and soundness assumption), to feed verifier cost models.
+
+use core::slice::from_ref;
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use p3_challenger::DuplexChallenger;
+use p3_commit::{BatchOpeningRef, Mmcs};
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::{Field, PrimeCharacteristicRing, extension::BinomialExtensionField};
+use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+use p3_matrix::{Dimensions, dense::RowMajorMatrix};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use whir_p3::{
+    constraints::{
+        Constraint,
+        evaluator::ConstraintPolyEvaluator,
+        statement::{EqStatement, SelectStatement},
+    },
+    fiat_shamir::domain_separator::DomainSeparator,
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        config::domain_separator,
+        proof::WhirProof,
+        prover::Prover,
+        verifier::Verifier,
+    },
+};
+
+type F = KoalaBear;
+type EF = BinomialExtensionField<F, 4>;
+
+type Poseidon16 = Poseidon2KoalaBear<16>;
+type Poseidon24 = Poseidon2KoalaBear<24>;
+
+type MerkleHash = PaddingFreeSponge<Poseidon24, 24, 16, 8>; // leaf hashing
+type MerkleCompress = TruncatedPermutation<Poseidon16, 2, 8, 16>; // 2-to-1 compression
+type MyChallenger = DuplexChallenger<F, Poseidon16, 16, 8>;
+type PackedF = <F as Field>::Packing;
+type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MerkleHash, MerkleCompress, 2, 8>;
+
+/// `(num_variables, folding_factor)` of the benchmarked configurations.
+const SHAPES: [(usize, usize); 4] = [(16, 2), (16, 4), (20, 4), (24, 4)];
+
+const SOUNDNESS_TYPES: [SecurityAssumption; 3] = [
+    SecurityAssumption::UniqueDecoding,
+    SecurityAssumption::JohnsonBound,
+    SecurityAssumption::CapacityBound,
+];
+
+/// A proof with everything needed to verify it.
+struct VerifierInputs {
+    params: WhirConfig<EF, F, MyMmcs, MyChallenger>,
+    challenger: MyChallenger,
+    domainsep: DomainSeparator<EF, F>,
+    statement: EqStatement<EF>,
+    proof: WhirProof<F, EF, MyMmcs>,
+}
+
+fn prepare_inputs(
+    num_variables: usize,
+    folding_factor: usize,
+    soundness_type: SecurityAssumption,
+) -> VerifierInputs {
+    let mut rng = SmallRng::seed_from_u64(1);
+    let poseidon16 = Poseidon16::new_from_rng_128(&mut rng);
+    let poseidon24 = Poseidon24::new_from_rng_128(&mut rng);
+    let mmcs = MyMmcs::new(
+        MerkleHash::new(poseidon24),
+        MerkleCompress::new(poseidon16.clone()),
+        0,
+    );
+
+    // No grinding, so that the benchmark measures the verifier checks only.
+    let whir_params = ProtocolParameters {
+        security_level: 100,
+        pow_bits: 0,
+        folding_factor: FoldingFactor::Constant(folding_factor),
+        mmcs,
+        soundness_type,
+        starting_log_inv_rate: 1,
+        rs_domain_initial_reduction_factor: 1,
+    };
+    let params = WhirConfig::new(num_variables, whir_params.clone());
+
+    let polynomial = Poly::<F>::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut initial_statement = params.initial_statement(polynomial, SumcheckStrategy::Svo);
+    let _ = initial_statement.evaluate(&Point::rand(&mut rng, num_variables));
+    let statement = initial_statement.normalize();
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    domainsep.add_whir_proof::<_, _, 8>(&params);
+    let challenger = MyChallenger::new(poseidon16);
+
+    let dft = Radix2DFTSmallBatch::<F>::new(1 << params.max_fft_size());
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &initial_statement.poly)
+        .unwrap();
+    let mut proof =
+        WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+    let mut prover_challenger = challenger.clone();
+    domainsep.observe_domain_separator(&mut prover_challenger);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut proof,
+            &mut prover_challenger,
+            &mut initial_statement,
+            root,
+            &prover_data,
+        )
+        .unwrap();
+
+    VerifierInputs {
+        params,
+        challenger,
+        domainsep,
+        statement,
+        proof,
+    }
+}
+
+/// Runs `bench` on every configuration of the grid, with its inputs.
+fn for_each_config(
+    c: &mut Criterion,
+    name: &str,
+    mut bench: impl FnMut(&mut Criterion, &str, &VerifierInputs),
+) {
+    for soundness_type in SOUNDNESS_TYPES {
+        for (num_variables, folding_factor) in SHAPES {
+            let inputs = prepare_inputs(num_variables, folding_factor, soundness_type);
+            let id = format!("{name}/{soundness_type:?}/nv{num_variables}_k{folding_factor}");
+            bench(c, &id, &inputs);
+        }
+    }
+}
+
+fn benchmark_verifier(c: &mut Criterion) {
+    for_each_config(c, "verifier", |c, id, inputs| {
+        let mut group = c.benchmark_group(id);
+        let params = &inputs.params;
+
+        // Full verification, including the commitment.
+        group.bench_function("verify", |b| {
+            b.iter(|| {
+                let mut challenger = inputs.challenger.clone();
+                inputs.domainsep.observe_domain_separator(&mut challenger);
+                let parsed_commitment = CommitmentReader::new(params)
+                    .parse_commitment::<F, 8>(&inputs.proof, &mut challenger)
+                    .unwrap();
+                Verifier::new(params)
+                    .verify(
+                        &inputs.proof,
+                        &mut challenger,
+                        &parsed_commitment,
+                        inputs.statement.clone(),
+                    )
+                    .unwrap()
+            });
+        });
+
+        // Commitment parsing: root and initial OOD answers.
+        group.bench_function("parse_commitment", |b| {
+            b.iter(|| {
+                let mut challenger = inputs.challenger.clone();
+                inputs.domainsep.observe_domain_separator(&mut challenger);
+                CommitmentReader::new(params)
+                    .parse_commitment::<F, 8>(&inputs.proof, &mut challenger)
+                    .unwrap()
+            });
+        });
+
+        // Merkle path checks of the first STIR phase, which opens the base field commitment.
+        let round = params
+            .round_parameters
+            .first()
+            .cloned()
+            .unwrap_or_else(|| params.final_round_config());
+        let (root, openings, dimensions) = merkle_openings(
+            &params.mmcs,
+            round.domain_size >> round.folding_factor,
+            1 << round.folding_factor,
+            round.num_queries,
+        );
+        group.bench_function(BenchmarkId::new("merkle_paths", round.num_queries), |b| {
+            b.iter(|| {
+                for (index, values, proof) in &openings {
+                    params
+                        .mmcs
+                        .verify_batch(
+                            &root,
+                            &dimensions,
+                            *index,
+                            BatchOpeningRef {
+                                opened_values: from_ref(values),
+                                opening_proof: proof,
+                            },
+                        )
+                        .unwrap();
+                }
+            });
+        });
+
+        // Evaluation of the combined constraint polynomial at the end of the protocol.
+        let (constraints, point) = constraints_of(params);
+        let evaluator = ConstraintPolyEvaluator::new(params.folding_factor);
+        group.bench_function("eval_constraints_poly", |b| {
+            b.iter(|| evaluator.eval_constraints_poly(&constraints, &point));
+        });
+
+        group.finish();
+    });
+}
+
+/// Commits to a random `height x width` matrix and opens `num_queries` random rows.
+#[allow(clippy::type_complexity)]
+fn merkle_openings(
+    mmcs: &MyMmcs,
+    height: usize,
+    width: usize,
+    num_queries: usize,
+) -> (
+    <MyMmcs as Mmcs<F>>::Commitment,
+    Vec<(usize, Vec<F>, <MyMmcs as Mmcs<F>>::Proof)>,
+    Vec<Dimensions>,
+) {
+    let mut rng = SmallRng::seed_from_u64(2);
+    let matrix = RowMajorMatrix::<F>::rand(&mut rng, height, width);
+    let (root, prover_data) = mmcs.commit_matrix(matrix);
+    let openings = (0..num_queries)
+        .map(|_| {
+            let index = rng.random::<u32>() as usize % height;
+            let mut opening = mmcs.open_batch(index, &prover_data);
+            (
+                index,
+                opening.opened_values.swap_remove(0),
+                opening.opening_proof,
+            )
+        })
+        .collect();
+    (root, openings, vec![Dimensions { height, width }])
+}
+
+/// Builds constraints shaped like the ones gathered by the verifier: the initial statement,
+/// then the OOD and STIR constraints of every round, and the point they are evaluated at.
+fn constraints_of(
+    params: &WhirConfig<EF, F, MyMmcs, MyChallenger>,
+) -> (Vec<Constraint<F, EF>>, Point<EF>) {
+    let mut rng = SmallRng::seed_from_u64(3);
+
+    let mut initial = EqStatement::initialize(params.num_variables);
+    initial.add_evaluated_constraint(Point::rand(&mut rng, params.num_variables), rng.random());
+    let mut constraints = vec![Constraint::new(
+        rng.random(),
+        initial,
+        SelectStatement::initialize(params.num_variables),
+    )];
+
+    for round in &params.round_parameters {
+        let mut ood_statement = EqStatement::initialize(round.num_variables);
+        for _ in 0..round.ood_samples {
+            ood_statement
+                .add_evaluated_constraint(Point::rand(&mut rng, round.num_variables), rng.random());
+        }
+        let (vars, evals) = (0..round.num_queries)
+            .map(|_| {
+                let var = round.folded_domain_gen.exp_u64(rng.random::<u32>().into());
+                (var, rng.random::<EF>())
+            })
+            .unzip();
+        constraints.push(Constraint::new(
+            rng.random(),
+            ood_statement,
+            SelectStatement::new(round.num_variables, vars, evals),
+        ));
+    }
+
+    (constraints, Point::rand(&mut rng, params.num_variables))
+}
+
+criterion_group!(benches, benchmark_verifier);
+criterion_main!(benches);
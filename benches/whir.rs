@@ -13,7 +13,10 @@ use whir_p3::{
         DEFAULT_MAX_POW, FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy,
         WhirConfig,
     },
-    whir::{committer::writer::CommitmentWriter, proof::WhirProof, prover::Prover},
+    whir::{
+        committer::writer::CommitmentWriter, config::domain_separator, proof::WhirProof,
+        prover::Prover,
+    },
 };
 
 type F = KoalaBear;
@@ -104,7 +107,7 @@ fn prepare_inputs() -> (
     // Fiat-Shamir setup
 
     // Create a domain separator for transcript hashing.
-    let mut domainsep = DomainSeparator::new(vec![]);
+    let mut domainsep = domain_separator(&params);
 
     // Commit protocol parameters and proof type to the domain separator.
     domainsep.commit_statement::<_, _, 32>(&params);
@@ -136,20 +139,8 @@ fn benchmark_commit_and_prove(c: &mut Criterion) {
 
     c.bench_function("commit", |b| {
         b.iter(|| {
-            let mut challenger_clone = challenger.clone();
-            domainsep.observe_domain_separator(&mut challenger_clone);
-            let mut proof =
-                WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
             let committer = CommitmentWriter::new(&params);
-            let mut initial_statement = initial_statement.clone();
-            let _prover_data = committer
-                .commit(
-                    &dft,
-                    &mut proof,
-                    &mut challenger_clone,
-                    &mut initial_statement,
-                )
-                .unwrap();
+            let _commitment = committer.commit(&dft, &initial_statement.poly).unwrap();
         });
     });
 
@@ -161,14 +152,7 @@ fn benchmark_commit_and_prove(c: &mut Criterion) {
                 WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
             let committer = CommitmentWriter::new(&params);
             let mut initial_statement = initial_statement.clone();
-            let prover_data = committer
-                .commit(
-                    &dft,
-                    &mut proof,
-                    &mut challenger_clone,
-                    &mut initial_statement,
-                )
-                .unwrap();
+            let (root, prover_data) = committer.commit(&dft, &initial_statement.poly).unwrap();
 
             let prover = Prover(&params);
             prover
@@ -176,8 +160,9 @@ fn benchmark_commit_and_prove(c: &mut Criterion) {
                     &dft,
                     &mut proof,
                     &mut challenger_clone,
-                    &initial_statement,
-                    prover_data,
+                    &mut initial_statement,
+                    root,
+                    &prover_data,
                 )
                 .unwrap();
         });
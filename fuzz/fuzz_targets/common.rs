@@ -0,0 +1,39 @@
+//! Types and helpers shared by the fuzz targets.
+
+#![allow(dead_code)]
+
+use bincode::Options;
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_field::{Field, extension::BinomialExtensionField};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use whir_p3::whir::proof::WhirProof;
+
+pub type F = BabyBear;
+pub type EF = BinomialExtensionField<F, 4>;
+pub type Perm = Poseidon2BabyBear<16>;
+
+pub type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+pub type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+pub type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+
+pub type PackedF = <F as Field>::Packing;
+pub type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
+
+pub type Proof = WhirProof<F, EF, MyMmcs>;
+
+/// Upper bound on the size of a serialized proof.
+///
+/// Bincode checks every length prefix against the remaining budget before allocating, so
+/// a forged prefix cannot make deserialization reserve more than this many bytes.
+pub const MAX_PROOF_BYTES: u64 = 1 << 24;
+
+/// Deserializes a proof with the encoding of `bincode::serialize`, under a size limit.
+pub fn deserialize_proof(bytes: &[u8]) -> bincode::Result<Proof> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_PROOF_BYTES)
+        .deserialize(bytes)
+}
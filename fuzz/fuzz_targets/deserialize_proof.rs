@@ -0,0 +1,17 @@
+//! Deserializes arbitrary bytes as a `WhirProof`.
+//!
+//! Any input must either fail to decode or yield a proof that re-serializes to a prefix of
+//! the input.
+
+#![no_main]
+
+mod common;
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(proof) = common::deserialize_proof(data) {
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert!(data.starts_with(&bytes));
+    }
+});
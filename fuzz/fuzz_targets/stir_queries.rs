@@ -0,0 +1,55 @@
+//! Samples STIR queries for fuzzed domain and folding parameters.
+//!
+//! Inputs are restricted to the preconditions guaranteed by `WhirConfig` (a power-of-two
+//! domain no larger than the two-adicity of the field, folded at most down to a single
+//! point); within them sampling must not panic and must return sorted, distinct, in-range
+//! indices.
+
+#![no_main]
+
+mod common;
+
+use std::sync::LazyLock;
+
+use common::{EF, F, MyChallenger, Perm};
+use libfuzzer_sys::{arbitrary::Arbitrary, fuzz_target};
+use p3_challenger::CanObserve;
+use p3_field::{PrimeCharacteristicRing, TwoAdicField};
+use rand::{SeedableRng, rngs::SmallRng};
+use whir_p3::whir::utils::get_challenge_stir_queries;
+
+/// Largest number of queries generated, above any configuration produced in practice.
+const MAX_NUM_QUERIES: usize = 1 << 10;
+
+static PERM: LazyLock<Perm> =
+    LazyLock::new(|| Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(0)));
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    log_domain_size: u8,
+    folding_factor: u8,
+    num_queries: u16,
+    seed: u32,
+}
+
+fuzz_target!(|input: Input| {
+    let log_domain_size = usize::from(input.log_domain_size) % (F::TWO_ADICITY + 1);
+    let folding_factor = usize::from(input.folding_factor) % (log_domain_size + 1);
+    let num_queries = usize::from(input.num_queries) % (MAX_NUM_QUERIES + 1);
+
+    let mut challenger = MyChallenger::new(PERM.clone());
+    challenger.observe(F::from_u32(input.seed));
+
+    let queries = get_challenge_stir_queries::<_, F, EF>(
+        1 << log_domain_size,
+        folding_factor,
+        num_queries,
+        &mut challenger,
+    )
+    .unwrap();
+
+    let folded_domain_size = 1usize << (log_domain_size - folding_factor);
+    assert!(queries.len() <= num_queries);
+    assert!(queries.windows(2).all(|w| w[0] < w[1]));
+    assert!(queries.iter().all(|&index| index < folded_domain_size));
+});
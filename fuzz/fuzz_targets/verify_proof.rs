@@ -0,0 +1,141 @@
+//! Runs `CommitmentReader::parse_commitment` and `Verifier::verify` on fuzzed proofs.
+//!
+//! Random bytes rarely decode into a proof of the right shape, so the input is interpreted
+//! in one of two ways depending on its first byte:
+//! - even: the remaining bytes are deserialized as is;
+//! - odd: the remaining bytes are read as 5-byte patches `(offset: u32 LE, xor: u8)` applied
+//!   to the serialization of an honest proof, which lets the fuzzer reach the later phases.
+//!
+//! The verifier must return an error, never panic, whatever the proof.
+
+#![no_main]
+
+mod common;
+
+use std::sync::LazyLock;
+
+use bincode::Options;
+use common::{EF, F, MyChallenger, MyCompress, MyHash, MyMmcs, Perm, Proof};
+use libfuzzer_sys::fuzz_target;
+use p3_dft::Radix2DFTSmallBatch;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use whir_p3::{
+    constraints::statement::EqStatement,
+    fiat_shamir::domain_separator::DomainSeparator,
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{config::domain_separator, 
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        prover::Prover,
+        verifier::Verifier,
+    },
+};
+
+/// A fixed small configuration with an honest proof, built once per fuzzing process.
+struct Setup {
+    params: WhirConfig<EF, F, MyMmcs, MyChallenger>,
+    challenger: MyChallenger,
+    domainsep: DomainSeparator<EF, F>,
+    statement: EqStatement<EF>,
+    proof_bytes: Vec<u8>,
+}
+
+static SETUP: LazyLock<Setup> = LazyLock::new(|| {
+    let num_variables = 6;
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()), 0);
+
+    let whir_params = ProtocolParameters {
+        security_level: 32,
+        pow_bits: 0,
+        rs_domain_initial_reduction_factor: 1,
+        folding_factor: FoldingFactor::Constant(2),
+        mmcs,
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+    };
+    let params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+    let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut initial_statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+    let _ = initial_statement.evaluate(&Point::rand(&mut rng, num_variables));
+    let statement = initial_statement.normalize();
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    domainsep.add_whir_proof::<_, _, 8>(&params);
+    let challenger = MyChallenger::new(perm);
+
+    let dft = Radix2DFTSmallBatch::<F>::default();
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &initial_statement.poly)
+        .unwrap();
+    let mut proof = Proof::from_protocol_parameters(&whir_params, num_variables);
+    let mut prover_challenger = challenger.clone();
+    domainsep.observe_domain_separator(&mut prover_challenger);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut proof,
+            &mut prover_challenger,
+            &mut initial_statement,
+            root,
+            &prover_data,
+        )
+        .unwrap();
+
+    Setup {
+        params,
+        challenger,
+        domainsep,
+        statement,
+        proof_bytes: bincode::serialize(&proof).unwrap(),
+    }
+});
+
+fn mutated_proof_bytes(patches: &[u8]) -> Vec<u8> {
+    let mut bytes = SETUP.proof_bytes.clone();
+    for patch in patches.chunks_exact(5) {
+        let offset = u32::from_le_bytes(patch[..4].try_into().unwrap()) as usize;
+        let len = bytes.len();
+        bytes[offset % len] ^= patch[4];
+    }
+    bytes
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&mode, rest)) = data.split_first() else {
+        return;
+    };
+    let bytes = if mode & 1 == 0 {
+        rest.to_vec()
+    } else {
+        mutated_proof_bytes(rest)
+    };
+    let setup = &*SETUP;
+    let options = bincode::DefaultOptions::new().with_fixint_encoding();
+    let Ok(proof) = Proof::deserialize_bounded(
+        &setup.params,
+        &mut bincode::Deserializer::from_slice(&bytes, options),
+    ) else {
+        return;
+    };
+
+    let mut challenger = setup.challenger.clone();
+    setup.domainsep.observe_domain_separator(&mut challenger);
+    let Ok(parsed_commitment) =
+        CommitmentReader::new(&setup.params).parse_commitment::<F, 8>(&proof, &mut challenger)
+    else {
+        return;
+    };
+    let _ = Verifier::new(&setup.params).verify(
+        &proof,
+        &mut challenger,
+        &parsed_commitment,
+        setup.statement.clone(),
+    );
+});
@@ -1,6 +1,12 @@
-use std::time::Instant;
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::PathBuf,
+    time::Instant,
+};
 
-use clap::Parser;
+use bincode::Options;
+use clap::{Parser, Subcommand};
 use p3_challenger::DuplexChallenger;
 use p3_dft::Radix2DFTSmallBatch;
 use p3_field::{Field, extension::BinomialExtensionField};
@@ -15,14 +21,16 @@ use rand::{
 use tracing_forest::{ForestLayer, util::LevelFilter};
 use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt, util::SubscriberInitExt};
 use whir_p3::{
-    fiat_shamir::domain_separator::DomainSeparator,
     parameters::{
         DEFAULT_MAX_POW, FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy,
         WhirConfig,
     },
     whir::{
         committer::{reader::CommitmentReader, writer::CommitmentWriter},
-        proof::WhirProof,
+        config::{WhirConfigExt, WhirConfigTryNew, domain_separator},
+        description::{ConfigDescription, MmcsRegistry},
+        optimizer::{Objective, WhirOptimize},
+        proof::{InitialClaims, WhirProof},
         prover::Prover,
         verifier::Verifier,
     },
@@ -40,6 +48,9 @@ type MyChallenger = DuplexChallenger<F, Poseidon16, 16, 8>;
 type PackedF = <F as Field>::Packing;
 type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MerkleHash, MerkleCompress, 2, 8>;
 
+/// Identifier of the Merkle tree MMCS in configuration files.
+const MMCS_ID: &str = "poseidon2-koalabear";
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -66,6 +77,40 @@ struct Args {
 
     #[arg(long = "initial-rs-reduction", default_value = "3")]
     rs_domain_initial_reduction_factor: usize,
+
+    /// Height of the Merkle cap: with trees of arity `k`, commitments publish `k^h` digests and
+    /// paths lose their top `h` levels. The trees built here are binary. Rejected if higher
+    /// than a committed tree.
+    #[arg(long = "cap-height", default_value = "0")]
+    cap_height: usize,
+
+    /// Read the protocol parameters and number of variables from this TOML file, instead of
+    /// the options above. Its `mmcs` must be `poseidon2-koalabear`.
+    #[arg(long = "config")]
+    config: Option<PathBuf>,
+
+    /// Write the proof, with its evaluation claims embedded, to this file.
+    #[arg(long = "proof-out")]
+    proof_out: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Verify a proof file written with `--proof-out` against the claims it embeds.
+    ///
+    /// The protocol parameters must match the ones used to produce the proof.
+    Verify { proof: PathBuf },
+
+    /// Search the parameters minimizing an objective, for the security level, soundness
+    /// assumption and number of variables given as options.
+    Optimize {
+        /// One of `ProofSize`, `ProverTime` or `VerifierHashes`.
+        #[arg(long, default_value = "ProofSize")]
+        objective: Objective,
+    },
 }
 
 #[allow(clippy::too_many_lines)]
@@ -105,24 +150,127 @@ fn main() {
 
     let merkle_hash = MerkleHash::new(poseidon24);
     let merkle_compress = MerkleCompress::new(poseidon16.clone());
-    let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
+    let mmcs = MyMmcs::new(merkle_hash, merkle_compress, args.cap_height);
+
+    if let Some(Command::Optimize { objective }) = &args.command {
+        let Some(whir_params) = WhirConfig::<EF, F, MyMmcs, MyChallenger>::optimize(
+            num_variables,
+            security_level,
+            soundness_type,
+            *objective,
+            mmcs,
+        ) else {
+            println!("No parameters reach {security_level} bits of security.");
+            std::process::exit(1);
+        };
+        let params =
+            WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+        let cost = params.estimate_cost();
+        // The folding schedule may change after the first round, which the options cannot
+        // express: print a configuration file instead.
+        let description = ConfigDescription::new(MMCS_ID, num_variables, &whir_params);
+        println!(
+            "Best parameters for {objective:?}, to pass with --config:\n{}",
+            toml::to_string(&description).expect("descriptions serialize to TOML")
+        );
+        println!(
+            "Estimated cost: {} proof bytes, {} prover operations, {} verifier hashes",
+            cost.proof_bytes, cost.prover_ops, cost.verifier_hashes
+        );
+        return;
+    }
 
     let rs_domain_initial_reduction_factor = args.rs_domain_initial_reduction_factor;
 
-    let num_coeffs = 1 << num_variables;
-
     // Construct WHIR protocol parameters
-    let whir_params = ProtocolParameters {
-        security_level,
-        pow_bits,
-        folding_factor,
-        mmcs,
-        soundness_type,
-        starting_log_inv_rate: starting_rate,
-        rs_domain_initial_reduction_factor,
+    let (num_variables, whir_params) = match &args.config {
+        Some(path) => {
+            let contents = fs::read_to_string(path).unwrap_or_else(|err| {
+                println!("Cannot read config file {}: {err}.", path.display());
+                std::process::exit(1);
+            });
+            let description: ConfigDescription = toml::from_str(&contents).unwrap_or_else(|err| {
+                println!("Invalid config file {}: {err}", path.display());
+                std::process::exit(1);
+            });
+            let registry = MmcsRegistry::new().with(MMCS_ID, mmcs);
+            let whir_params = description
+                .protocol_parameters(&registry)
+                .unwrap_or_else(|err| {
+                    println!("Invalid config file: {err}.");
+                    std::process::exit(1);
+                });
+            (description.num_variables, whir_params)
+        }
+        None => (
+            num_variables,
+            ProtocolParameters {
+                security_level,
+                pow_bits,
+                folding_factor,
+                mmcs,
+                soundness_type,
+                starting_log_inv_rate: starting_rate,
+                rs_domain_initial_reduction_factor,
+            },
+        ),
     };
+    let num_coeffs = 1 << num_variables;
+
+    let params =
+        WhirConfig::<EF, F, MyMmcs, MyChallenger>::try_new(num_variables, whir_params.clone())
+            .unwrap_or_else(|err| {
+                println!("Invalid parameters: {err}.");
+                std::process::exit(1);
+            });
+
+    // Define the Fiat-Shamir domain separator pattern for committing and proving
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 32>(&params);
+    domainsep.add_whir_proof::<_, _, 32>(&params);
 
-    let params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+    let challenger = MyChallenger::new(poseidon16);
+
+    if let Some(Command::Verify { proof }) = &args.command {
+        // Bounded by the configuration, so that a forged proof cannot exhaust memory.
+        let file = File::open(proof).unwrap_or_else(|err| {
+            eprintln!("Cannot open proof file {}: {err}.", proof.display());
+            std::process::exit(1);
+        });
+        let options = bincode::DefaultOptions::new().with_fixint_encoding();
+        let proof = WhirProof::<F, EF, MyMmcs>::deserialize_bounded(
+            &params,
+            &mut bincode::Deserializer::with_reader(BufReader::new(file), options),
+        )
+        .unwrap_or_else(|err| {
+            eprintln!("Proof is invalid: cannot deserialize it: {err}.");
+            std::process::exit(1);
+        });
+
+        let mut verifier_challenger = challenger;
+        domainsep.observe_domain_separator(&mut verifier_challenger);
+
+        let verif_time = Instant::now();
+        let result = CommitmentReader::new(&params)
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .and_then(|parsed_commitment| {
+                Verifier::new(&params).verify_standalone(
+                    &proof,
+                    &mut verifier_challenger,
+                    &parsed_commitment,
+                )
+            });
+        let verify_time = verif_time.elapsed();
+
+        match result {
+            Ok(_) => println!("Proof is valid ({} μs)", verify_time.as_micros()),
+            Err(err) => {
+                eprintln!("Proof is invalid: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
     let mut rng = StdRng::seed_from_u64(0);
     let polynomial = Poly::<F>::new((0..num_coeffs).map(|_| rng.random()).collect());
@@ -136,18 +284,26 @@ fn main() {
 
     let verifier_statement = initial_statement.normalize();
 
-    // Define the Fiat-Shamir domain separator pattern for committing and proving
-    let mut domainsep = DomainSeparator::new(vec![]);
-    domainsep.commit_statement::<_, _, 32>(&params);
-    domainsep.add_whir_proof::<_, _, 32>(&params);
-
     println!("=========================================");
     println!("Whir (PCS) 🌪️");
     if !params.check_pow_bits() {
         println!("WARN: more PoW bits required than what specified.");
     }
-
-    let challenger = MyChallenger::new(poseidon16);
+    if args.cap_height > 0 {
+        match params.merkle_cap_report() {
+            Ok(report) => println!(
+                "Merkle cap of height {}: +{} cap digests, -{} path digests (net {:+} digests)",
+                args.cap_height,
+                report.cap_digests,
+                report.path_digests_saved,
+                report.net_digests()
+            ),
+            Err(err) => {
+                println!("Invalid Merkle cap: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
 
     // Initialize the prover's challenger with domain separator
     let mut prover_challenger = challenger.clone();
@@ -161,15 +317,13 @@ fn main() {
     let mut proof =
         WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
 
+    // Embed the claims so that the written proof can be verified on its own
+    if args.proof_out.is_some() {
+        proof.claims = Some(InitialClaims::new(num_variables, &verifier_statement));
+    }
+
     let time = Instant::now();
-    let prover_data = committer
-        .commit(
-            &dft,
-            &mut proof,
-            &mut prover_challenger,
-            &mut initial_statement,
-        )
-        .unwrap();
+    let (root, prover_data) = committer.commit(&dft, &initial_statement.poly).unwrap();
     let commit_time = time.elapsed();
 
     // Generate a proof using the prover
@@ -182,8 +336,9 @@ fn main() {
             &dft,
             &mut proof,
             &mut prover_challenger,
-            &initial_statement,
-            prover_data,
+            &mut initial_statement,
+            root,
+            &prover_data,
         )
         .unwrap();
 
@@ -200,8 +355,9 @@ fn main() {
     domainsep.observe_domain_separator(&mut verifier_challenger);
 
     // Parse the commitment
-    let parsed_commitment =
-        commitment_reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+    let parsed_commitment = commitment_reader
+        .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+        .unwrap();
 
     let verif_time = Instant::now();
     verifier
@@ -220,11 +376,22 @@ fn main() {
         commit_time.as_millis(),
         opening_time.as_millis()
     );
-    let proof_bytes = bincode::serialize(&proof).expect("Failed to serialize proof");
+    let proof_bytes = bincode::serialize(&proof).unwrap_or_else(|err| {
+        eprintln!("Cannot serialize the proof: {err}.");
+        std::process::exit(1);
+    });
     println!(
         "Proof size: {} bytes ({:.2} KB)",
         proof_bytes.len(),
         proof_bytes.len() as f64 / 1024.0
     );
     println!("Verification time: {} μs", verify_time.as_micros());
+
+    if let Some(path) = &args.proof_out {
+        if let Err(err) = fs::write(path, &proof_bytes) {
+            eprintln!("Cannot write proof file {}: {err}.", path.display());
+            std::process::exit(1);
+        }
+        println!("Proof written to {}", path.display());
+    }
 }
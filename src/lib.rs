@@ -1,5 +1,7 @@
 #![no_std]
 extern crate alloc;
+#[cfg(any(test, feature = "std"))]
+extern crate std;
 
 pub use p3_whir::{constraints, fiat_shamir, parameters, sumcheck};
 pub mod whir;
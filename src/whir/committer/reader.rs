@@ -1,11 +1,23 @@
-use core::{fmt::Debug, ops::Deref};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::ops::Deref;
 
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field, PackedValue, TwoAdicField};
 use p3_multilinear_util::point::Point;
 
-use crate::{constraints::statement::EqStatement, parameters::WhirConfig, whir::proof::WhirProof};
+use crate::{
+    constraints::statement::EqStatement,
+    parameters::WhirConfig,
+    whir::{
+        mmcs::{RoundCommitment, WhirMmcs},
+        proof::WhirProof,
+        verifier::errors::{VerifierError, VerifierPhase},
+    },
+};
 
 /// Represents a parsed commitment from the prover in the WHIR protocol.
 ///
@@ -28,7 +40,7 @@ impl<F, D> ParsedCommitment<F, D>
 where
     F: Field,
 {
-    /// Parse a commitment from the verifier's transcript state.
+    /// Parse the initial commitment from the verifier's transcript state.
     ///
     /// This function extracts a `ParsedCommitment` by reading the Merkle root,
     /// out-of-domain (OOD) challenge points, and corresponding claimed evaluations
@@ -50,49 +62,99 @@ where
     /// - The prover's claimed answers at those points.
     ///
     /// This is used to verify consistency of polynomial commitments in WHIR.
-    pub fn parse<EF, MT: Mmcs<F>, Challenger>(
+    ///
+    /// # Errors
+    /// Returns `VerifierError::MalformedProof` if the proof lacks the commitment or does not
+    /// carry exactly `ood_samples` OOD answers.
+    pub fn parse<EF, MT: WhirMmcs<F>, Challenger>(
         proof: &WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
         num_variables: usize,
         ood_samples: usize,
-    ) -> ParsedCommitment<EF, MT::Commitment>
+    ) -> Result<ParsedCommitment<EF, MT::Commitment>, VerifierError>
     where
         F: TwoAdicField,
         EF: ExtensionField<F> + TwoAdicField,
         Challenger:
             FieldChallenger<F> + GrindingChallenger<Witness = F> + CanObserve<MT::Commitment>,
     {
-        Self::parse_with_round(proof, challenger, num_variables, ood_samples, None)
+        Self::observe_commitment(
+            proof.initial_commitment.as_ref(),
+            &proof.initial_ood_answers,
+            challenger,
+            num_variables,
+            ood_samples,
+            0,
+        )
     }
 
-    pub fn parse_with_round<EF, MT: Mmcs<F>, Challenger>(
+    /// Parse the commitment of round `round_index`, made with the round MMCS of `MT`.
+    ///
+    /// # Errors
+    /// Same as [`Self::parse`], and `VerifierError::MalformedProof` if the proof has no
+    /// round `round_index`.
+    pub fn parse_round<EF, MT: WhirMmcs<F>, Challenger>(
         proof: &WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
         num_variables: usize,
         ood_samples: usize,
-        round_index: Option<usize>,
-    ) -> ParsedCommitment<EF, MT::Commitment>
+        round_index: usize,
+    ) -> Result<ParsedCommitment<EF, RoundCommitment<F, MT>>, VerifierError>
     where
         F: TwoAdicField,
         EF: ExtensionField<F> + TwoAdicField,
-        Challenger:
-            FieldChallenger<F> + GrindingChallenger<Witness = F> + CanObserve<MT::Commitment>,
+        Challenger: FieldChallenger<F>
+            + GrindingChallenger<Witness = F>
+            + CanObserve<RoundCommitment<F, MT>>,
     {
-        let (root, ood_answers) = round_index.map_or_else(
-            || {
-                (
-                    proof.initial_commitment.clone().unwrap(),
-                    proof.initial_ood_answers.clone(),
-                )
-            },
-            |idx| {
-                let round_proof = &proof.rounds[idx];
-                (
-                    round_proof.commitment.clone().unwrap(),
-                    round_proof.ood_answers.clone(),
-                )
-            },
-        );
+        let round_proof =
+            proof
+                .rounds
+                .get(round_index)
+                .ok_or_else(|| VerifierError::MalformedProof {
+                    round: round_index,
+                    phase: VerifierPhase::Ood,
+                    details: "round not found in proof".to_string(),
+                })?;
+        Self::observe_commitment(
+            round_proof.commitment.as_ref(),
+            &round_proof.ood_answers,
+            challenger,
+            num_variables,
+            ood_samples,
+            round_index,
+        )
+    }
+
+    /// Observes a root and its OOD answers, whatever the MMCS that produced the root.
+    fn observe_commitment<EF, C, Challenger>(
+        commitment: Option<&C>,
+        ood_answers: &[EF],
+        challenger: &mut Challenger,
+        num_variables: usize,
+        ood_samples: usize,
+        round_index: usize,
+    ) -> Result<ParsedCommitment<EF, C>, VerifierError>
+    where
+        F: TwoAdicField,
+        EF: ExtensionField<F> + TwoAdicField,
+        C: Clone,
+        Challenger: FieldChallenger<F> + GrindingChallenger<Witness = F> + CanObserve<C>,
+    {
+        let malformed = |details: String| VerifierError::MalformedProof {
+            round: round_index,
+            phase: VerifierPhase::Ood,
+            details,
+        };
+        let root = commitment
+            .cloned()
+            .ok_or_else(|| malformed("missing commitment".to_string()))?;
+        if ood_answers.len() != ood_samples {
+            return Err(malformed(format!(
+                "expected {ood_samples} OOD answers, got {}",
+                ood_answers.len()
+            )));
+        }
 
         // Observe the root in the challenger using generic CanObserve
         challenger.observe(root.clone());
@@ -111,10 +173,10 @@ where
         });
 
         // Return a structured representation of the commitment.
-        ParsedCommitment {
+        Ok(ParsedCommitment {
             root,
             ood_statement,
-        }
+        })
     }
 }
 
@@ -139,7 +201,7 @@ where
     F: TwoAdicField,
     EF: ExtensionField<F> + TwoAdicField,
     Challenger: FieldChallenger<F> + GrindingChallenger<Witness = F>,
-    MT: Mmcs<F>,
+    MT: WhirMmcs<F>,
 {
     /// Create a new commitment reader from a WHIR configuration.
     ///
@@ -152,11 +214,18 @@ where
     ///
     /// Reads the Merkle root and out-of-domain (OOD) challenge points and answers
     /// expected for verifying the committed polynomial.
+    ///
+    /// The configuration fingerprint is not absorbed here: the transcript starts with it, see
+    /// [`crate::whir::config::domain_separator`].
+    ///
+    /// # Errors
+    /// Returns `VerifierError::MalformedProof` if the proof lacks the initial commitment or
+    /// does not carry the expected number of OOD answers.
     pub fn parse_commitment<W, const DIGEST_ELEMS: usize>(
         &self,
         proof: &WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
-    ) -> ParsedCommitment<EF, MT::Commitment>
+    ) -> Result<ParsedCommitment<EF, MT::Commitment>, VerifierError>
     where
         W: PackedValue<Value = W> + Eq + Copy,
         Challenger: CanObserve<MT::Commitment>,
@@ -185,8 +254,6 @@ where
 
 #[cfg(test)]
 mod tests {
-    use alloc::vec;
-
     use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
     use p3_challenger::DuplexChallenger;
     use p3_dft::Radix2DFTSmallBatch;
@@ -198,9 +265,8 @@ mod tests {
 
     use super::*;
     use crate::{
-        fiat_shamir::domain_separator::DomainSeparator,
         parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy},
-        whir::{committer::writer::CommitmentWriter, proof::WhirProof},
+        whir::{committer::writer::CommitmentWriter, proof::WhirProof, prover::Prover},
     };
 
     type F = BabyBear;
@@ -275,7 +341,7 @@ mod tests {
         let dft = Radix2DFTSmallBatch::<F>::default();
 
         // Set up Fiat-Shamir transcript and commit the protocol parameters.
-        let mut ds = DomainSeparator::new(vec![]);
+        let mut ds = domain_separator(&params);
         ds.commit_statement::<_, _, 8>(&params);
 
         // Create the prover state from the transcript.
@@ -284,11 +350,17 @@ mod tests {
         let mut prover_challenger = challenger.clone();
         ds.observe_domain_separator(&mut prover_challenger);
 
+        // Commit the polynomial and obtain the root and Merkle tree.
+        let (root, prover_data) = committer.commit(&dft, &polynomial).unwrap();
+
+        // Open the commitment: observe the root and answer the OOD queries.
         let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
-        // Commit the polynomial and obtain a witness (root, Merkle proof, OOD evaluations).
-        let prover_data = committer
-            .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-            .unwrap();
+        Prover(&params).observe_commitment(
+            &mut proof,
+            &mut prover_challenger,
+            &mut statement,
+            root,
+        );
 
         // Simulate verifier state using transcript view of prover's nonce string.
         let mut verifier_challenger = challenger;
@@ -296,7 +368,9 @@ mod tests {
 
         // Create a commitment reader and parse the commitment from verifier state.
         let reader = CommitmentReader::new(&params);
-        let parsed = reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+        let parsed = reader
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .unwrap();
 
         // Ensure the Merkle root matches between prover and parsed result.
         assert_eq!(parsed.root, prover_data.root().into());
@@ -318,7 +392,7 @@ mod tests {
         let dft = Radix2DFTSmallBatch::<F>::default();
 
         // Begin the transcript and commit to the statement parameters.
-        let mut ds = DomainSeparator::new(vec![]);
+        let mut ds = domain_separator(&params);
         ds.commit_statement::<_, _, 8>(&params);
 
         // Create the prover state from the transcript.
@@ -327,11 +401,15 @@ mod tests {
         let mut prover_challenger = challenger.clone();
         ds.observe_domain_separator(&mut prover_challenger);
 
+        // Commit the polynomial and open the commitment.
+        let (root, prover_data) = committer.commit(&dft, &polynomial).unwrap();
         let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
-        // Commit the polynomial to obtain the witness.
-        let prover_data = committer
-            .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-            .unwrap();
+        Prover(&params).observe_commitment(
+            &mut proof,
+            &mut prover_challenger,
+            &mut statement,
+            root,
+        );
 
         // Initialize the verifier view of the transcript.
         let mut verifier_challenger = challenger;
@@ -339,7 +417,9 @@ mod tests {
 
         // Parse the commitment from verifier transcript.
         let reader = CommitmentReader::new(&params);
-        let parsed = reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+        let parsed = reader
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .unwrap();
 
         // Validate the Merkle root matches.
         assert_eq!(parsed.root, prover_data.root().into());
@@ -361,7 +441,7 @@ mod tests {
         let dft = Radix2DFTSmallBatch::<F>::default();
 
         // Start a new transcript and commit to the public parameters.
-        let mut ds = DomainSeparator::new(vec![]);
+        let mut ds = domain_separator(&params);
         ds.commit_statement::<_, _, 8>(&params);
 
         // Create prover state from the transcript.
@@ -370,11 +450,15 @@ mod tests {
         let mut prover_challenger = challenger.clone();
         ds.observe_domain_separator(&mut prover_challenger);
 
+        // Commit the polynomial and open the commitment.
+        let (root, prover_data) = committer.commit(&dft, &polynomial).unwrap();
         let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
-        // Commit the polynomial and obtain the witness.
-        let prover_data = committer
-            .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-            .unwrap();
+        Prover(&params).observe_commitment(
+            &mut proof,
+            &mut prover_challenger,
+            &mut statement,
+            root,
+        );
 
         // Initialize verifier view from prover's transcript string.
         let mut verifier_challenger = challenger;
@@ -382,7 +466,9 @@ mod tests {
 
         // Parse the commitment from verifier's transcript.
         let reader = CommitmentReader::new(&params);
-        let parsed = reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+        let parsed = reader
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .unwrap();
 
         // Check Merkle root and OOD answers match.
         assert_eq!(parsed.root, prover_data.root().into());
@@ -402,7 +488,7 @@ mod tests {
         let dft = Radix2DFTSmallBatch::<F>::default();
 
         // Set up Fiat-Shamir transcript and commit to the public parameters.
-        let mut ds = DomainSeparator::new(vec![]);
+        let mut ds = domain_separator(&params);
         ds.commit_statement::<_, _, 8>(&params);
 
         // Create the prover state from the transcript.
@@ -411,10 +497,14 @@ mod tests {
         let mut prover_challenger = challenger.clone();
         ds.observe_domain_separator(&mut prover_challenger);
 
+        let (root, _) = committer.commit(&dft, &polynomial).unwrap();
         let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
-        let _ = committer
-            .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-            .unwrap();
+        Prover(&params).observe_commitment(
+            &mut proof,
+            &mut prover_challenger,
+            &mut statement,
+            root,
+        );
 
         // Initialize the verifier view of the transcript.
         let mut verifier_challenger = challenger;
@@ -422,7 +512,9 @@ mod tests {
 
         // Parse the commitment from the verifier's state.
         let reader = CommitmentReader::new(&params);
-        let parsed = reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+        let parsed = reader
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .unwrap();
 
         // Each constraint should have correct univariate weight, sum, and flag.
         for (i, (point, &eval)) in parsed.ood_statement.iter().enumerate() {
@@ -1,18 +1,16 @@
 use core::ops::Deref;
 
-use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
+use p3_challenger::{FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::{Matrix, dense::RowMajorMatrixView};
-use p3_multilinear_util::point::Point;
+use p3_multilinear_util::poly::Poly;
 use tracing::{info_span, instrument};
 
 use crate::{
-    constraints::statement::initial::InitialStatement,
-    fiat_shamir::errors::FiatShamirError,
     parameters::WhirConfig,
-    whir::{committer::DenseMatrix, proof::WhirProof},
+    whir::{committer::DenseMatrix, prover::errors::ProverError},
 };
 
 /// Responsible for committing polynomials using a Merkle-based scheme.
@@ -47,29 +45,39 @@ where
     /// This function:
     /// - Expands polynomial coefficients to evaluations.
     /// - Applies folding and restructuring optimizations.
-    /// - Converts evaluations to an extension field.
     /// - Constructs a Merkle tree from the evaluations.
-    /// - Computes out-of-domain (OOD) challenge points and their evaluations.
-    /// - Returns a `Witness` containing the commitment data.
+    /// - Returns the Merkle root together with the prover data needed to open it.
+    ///
+    /// Committing does not touch any transcript: the root is observed and the out-of-domain
+    /// (OOD) queries are answered by `Prover::prove` when the commitment is opened. A polynomial
+    /// can therefore be committed before the opening transcript exists, and opened later.
+    ///
+    /// # Errors
+    /// Returns [`ProverError::PolynomialSize`] if the polynomial does not have the number of
+    /// variables of the configuration.
     #[instrument(skip_all)]
     pub fn commit<Dft>(
         &self,
         dft: &Dft,
-        proof: &mut WhirProof<F, EF, MT>,
-        challenger: &mut Challenger,
-        statement: &mut InitialStatement<F, EF>,
-    ) -> Result<MT::ProverData<DenseMatrix<F>>, FiatShamirError>
+        polynomial: &Poly<F>,
+    ) -> Result<(MT::Commitment, MT::ProverData<DenseMatrix<F>>), ProverError>
     where
         Dft: TwoAdicSubgroupDft<F>,
-        Challenger: CanObserve<MT::Commitment>,
     {
+        if polynomial.num_vars() != self.num_variables {
+            return Err(ProverError::PolynomialSize {
+                expected: self.num_variables,
+                actual: polynomial.num_vars(),
+            });
+        }
+
         // Transpose for reverse variable order
         // And then pad with zeros
 
         let padded = info_span!("transpose & pad").in_scope(|| {
-            let num_vars = statement.num_variables();
+            let num_vars = polynomial.num_vars();
             let mut mat = RowMajorMatrixView::new(
-                statement.poly.as_slice(),
+                polynomial.as_slice(),
                 1 << (num_vars - self.folding_factor.at_round(0)),
             )
             .transpose();
@@ -84,27 +92,7 @@ where
         let folded_matrix = info_span!("dft", height = padded.height(), width = padded.width())
             .in_scope(|| dft.dft_batch(padded).to_row_major_matrix());
 
-        let (root, prover_data) =
-            info_span!("commit_matrix").in_scope(|| self.mmcs.commit_matrix(folded_matrix));
-
-        proof.initial_commitment = Some(root.clone());
-        // Use CanObserve<Hash<F, W, N>> which both DuplexChallenger and SerializingChallenger implement
-        challenger.observe(root);
-
-        // TODO: consider moving ood sampling to whir::Prover::prove
-        (0..self.0.commitment_ood_samples).for_each(|_| {
-            // Generate OOD points from ProverState randomness
-            let point = Point::expand_from_univariate(
-                challenger.sample_algebra_element(),
-                self.num_variables,
-            );
-            let eval = info_span!("ood evaluation").in_scope(|| statement.evaluate(&point));
-            proof.initial_ood_answers.push(eval);
-            challenger.observe_algebra_element(eval);
-        });
-
-        // Return the prover data
-        Ok(prover_data)
+        Ok(info_span!("commit_matrix").in_scope(|| self.mmcs.commit_matrix(folded_matrix)))
     }
 }
 
@@ -128,15 +116,11 @@ mod tests {
     use p3_challenger::DuplexChallenger;
     use p3_dft::Radix2DFTSmallBatch;
     use p3_merkle_tree::MerkleTreeMmcs;
-    use p3_multilinear_util::poly::Poly;
     use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
     use rand::{RngExt, SeedableRng, rngs::SmallRng};
 
     use super::*;
-    use crate::{
-        fiat_shamir::domain_separator::DomainSeparator,
-        parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy},
-    };
+    use crate::parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption};
 
     type F = BabyBear;
     type Perm = Poseidon2BabyBear<16>;
@@ -146,16 +130,8 @@ mod tests {
     type PackedF = <F as Field>::Packing;
     type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
 
-    #[test]
-    fn test_basic_commitment() {
-        // Set up Whir protocol parameters.
-        let security_level = 100;
-        let pow_bits = 20;
-        let num_variables = 5;
-        let starting_rate = 1;
-        let folding_factor = 4;
-        let first_round_folding_factor = 4;
-
+    /// Builds a WHIR configuration over BabyBear with the given number of variables.
+    fn make_test_params(num_variables: usize) -> WhirConfig<F, F, MyMmcs, MyChallenger> {
         let mut rng = SmallRng::seed_from_u64(1);
         let perm = Perm::new_from_rng_128(&mut rng);
 
@@ -164,180 +140,92 @@ mod tests {
         let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
 
         let whir_params = ProtocolParameters {
-            security_level,
-            pow_bits,
+            security_level: 100,
+            pow_bits: 20,
             rs_domain_initial_reduction_factor: 1,
-            folding_factor: FoldingFactor::ConstantFromSecondRound(
-                first_round_folding_factor,
-                folding_factor,
-            ),
+            folding_factor: FoldingFactor::ConstantFromSecondRound(4, 4),
             mmcs,
             soundness_type: SecurityAssumption::CapacityBound,
-            starting_log_inv_rate: starting_rate,
+            starting_log_inv_rate: 1,
         };
 
-        // Define multivariate parameters for the polynomial.
-        let params =
-            WhirConfig::<F, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+        WhirConfig::new(num_variables, whir_params)
+    }
+
+    #[test]
+    fn test_basic_commitment() {
+        let params = make_test_params(5);
 
         // Generate a random polynomial with 32 coefficients.
         let mut rng = SmallRng::seed_from_u64(1);
         let polynomial = Poly::<BabyBear>::new(vec![rng.random(); 32]);
 
-        let mut proof =
-            WhirProof::<F, F, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
-
-        // Set up the DomainSeparator and initialize a ProverState narg_string.
-        let mut domainsep: DomainSeparator<F, F> = DomainSeparator::new(vec![]);
-        domainsep.commit_statement::<_, _, 8>(&params);
-        domainsep.add_whir_proof::<_, _, 8>(&params);
-
-        let mut rng = SmallRng::seed_from_u64(1);
-        let mut challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
-        domainsep.observe_domain_separator(&mut challenger);
-
-        let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
         // Run the Commitment Phase
         let committer = CommitmentWriter::new(&params);
         let dft = Radix2DFTSmallBatch::<F>::default();
-        let _ = committer
-            .commit(&dft, &mut proof, &mut challenger, &mut statement)
-            .unwrap();
+        let (root, prover_data) = committer.commit(&dft, &polynomial).unwrap();
 
-        // Ensure OOD (out-of-domain) points are generated.
-        assert!(!statement.is_empty(), "OOD points should be generated");
-
-        // Validate the number of generated OOD points.
-        assert_eq!(
-            statement.len(),
-            params.commitment_ood_samples,
-            "OOD points count should match expected samples"
-        );
-
-        // Check that OOD answers match expected evaluations
-        let poly = &statement.poly;
-        let statement = statement.normalize();
-        for (i, (ood_point, ood_eval)) in statement.iter().enumerate() {
-            let expected_eval = poly.eval_base(ood_point);
-            assert_eq!(
-                *ood_eval, expected_eval,
-                "OOD answer at index {i} should match expected evaluation"
-            );
-        }
+        // The returned root is the root of the returned Merkle tree.
+        assert_eq!(root, prover_data.root().into());
     }
 
     #[test]
     fn test_large_polynomial() {
-        let security_level = 100;
-        let pow_bits = 20;
-        let num_variables = 10;
-        let starting_rate = 1;
-        let folding_factor = 4;
-        let first_round_folding_factor = 4;
-
-        let mut rng = SmallRng::seed_from_u64(1);
-        let perm = Perm::new_from_rng_128(&mut rng);
-
-        let merkle_hash = MyHash::new(perm.clone());
-        let merkle_compress = MyCompress::new(perm);
-        let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
-
-        let whir_params = ProtocolParameters {
-            security_level,
-            pow_bits,
-            rs_domain_initial_reduction_factor: 1,
-            folding_factor: FoldingFactor::ConstantFromSecondRound(
-                first_round_folding_factor,
-                folding_factor,
-            ),
-            mmcs,
-            soundness_type: SecurityAssumption::CapacityBound,
-            starting_log_inv_rate: starting_rate,
-        };
-
-        let params =
-            WhirConfig::<F, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+        let params = make_test_params(10);
 
         let mut rng = SmallRng::seed_from_u64(1);
         let polynomial = Poly::<BabyBear>::new(vec![rng.random(); 1024]);
 
-        let mut proof =
-            WhirProof::<F, F, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
-
-        let mut domainsep = DomainSeparator::new(vec![]);
-        domainsep.commit_statement::<_, _, 8>(&params);
-
-        let mut rng = SmallRng::seed_from_u64(1);
-        let mut challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
-        domainsep.observe_domain_separator(&mut challenger);
-
-        let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
         let dft = Radix2DFTSmallBatch::<F>::default();
         let committer = CommitmentWriter::new(&params);
-        let _ = committer
-            .commit(&dft, &mut proof, &mut challenger, &mut statement)
-            .unwrap();
+        let (root, prover_data) = committer.commit(&dft, &polynomial).unwrap();
+
+        assert_eq!(root, prover_data.root().into());
     }
 
     #[test]
-    fn test_commitment_without_ood_samples() {
-        let security_level = 100;
-        let pow_bits = 20;
-        let num_variables = 5;
-        let starting_rate = 1;
-        let folding_factor = 4;
-        let first_round_folding_factor = 4;
-
-        let mut rng = SmallRng::seed_from_u64(1);
-        let perm = Perm::new_from_rng_128(&mut rng);
+    fn test_commitment_is_independent_of_ood_samples() {
+        let params = make_test_params(5);
 
-        let merkle_hash = MyHash::new(perm.clone());
-        let merkle_compress = MyCompress::new(perm);
-        let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
-
-        let whir_params = ProtocolParameters {
-            security_level,
-            pow_bits,
-            rs_domain_initial_reduction_factor: 1,
-            folding_factor: FoldingFactor::ConstantFromSecondRound(
-                first_round_folding_factor,
-                folding_factor,
-            ),
-            mmcs,
-            soundness_type: SecurityAssumption::CapacityBound,
-            starting_log_inv_rate: starting_rate,
-        };
-
-        let mut params =
-            WhirConfig::<F, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
-
-        // Explicitly set OOD samples to 0
-        params.commitment_ood_samples = 0;
-
-        let mut rng = SmallRng::seed_from_u64(1);
-        let polynomial = Poly::<BabyBear>::new(vec![rng.random(); 32]);
-
-        let mut proof =
-            WhirProof::<F, F, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
-
-        let mut domainsep = DomainSeparator::new(vec![]);
-        domainsep.commit_statement::<_, _, 8>(&params);
+        // Same configuration, but without any OOD samples.
+        let mut params_without_ood = make_test_params(5);
+        params_without_ood.commitment_ood_samples = 0;
 
         let mut rng = SmallRng::seed_from_u64(1);
-        let mut challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+        let polynomial = Poly::<BabyBear>::new((0..32).map(|_| rng.random()).collect());
+        let dft = Radix2DFTSmallBatch::<F>::default();
 
-        domainsep.observe_domain_separator(&mut challenger);
+        // OOD queries are answered when opening, so they have no influence on the commitment.
+        let (root, _) = CommitmentWriter::new(&params)
+            .commit(&dft, &polynomial)
+            .unwrap();
+        let (root_without_ood, _) = CommitmentWriter::new(&params_without_ood)
+            .commit(&dft, &polynomial)
+            .unwrap();
+        assert_eq!(root, root_without_ood);
 
-        let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
-        let dft = Radix2DFTSmallBatch::<F>::default();
-        let committer = CommitmentWriter::new(&params);
-        let _ = committer
-            .commit(&dft, &mut proof, &mut challenger, &mut statement)
+        // Committing is deterministic.
+        let (root_again, _) = CommitmentWriter::new(&params)
+            .commit(&dft, &polynomial)
             .unwrap();
+        assert_eq!(root, root_again);
+    }
 
-        assert!(
-            statement.is_empty(),
-            "There should be no OOD points when committment_ood_samples is 0"
-        );
+    #[test]
+    fn test_commit_rejects_polynomial_size() {
+        let params = make_test_params(5);
+        let mut rng = SmallRng::seed_from_u64(1);
+        let polynomial = Poly::<BabyBear>::new(vec![rng.random(); 64]);
+
+        let result =
+            CommitmentWriter::new(&params).commit(&Radix2DFTSmallBatch::default(), &polynomial);
+
+        assert!(matches!(
+            result,
+            Err(ProverError::PolynomialSize {
+                expected: 5,
+                actual: 6
+            })
+        ));
     }
 }
@@ -0,0 +1,709 @@
+//! Extensions of the WHIR configuration.
+
+use alloc::vec::Vec;
+
+use p3_commit::Mmcs;
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::CryptographicHasher;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    fiat_shamir::domain_separator::DomainSeparator,
+    parameters::{ProtocolParameters, SecurityAssumption, WhirConfig},
+    whir::{
+        mmcs::{MerkleShape, RoundCommitment, WhirMmcs, probe_matrix},
+        presets::{ExtensionFieldTooSmall, check_extension_field},
+    },
+};
+
+/// Digest identifying a WHIR configuration.
+pub type ConfigFingerprint = [u8; 32];
+
+/// Proof-size effect of committing with a Merkle cap, counted in digests.
+///
+/// A cap of height `h` publishes the `arity^h` nodes at depth `h` of each tree instead of its
+/// root, and every opening path stops `h` levels short of the root. Soundness is unchanged: the
+/// cap is observed by the transcript exactly as a root would be, so only the proof size moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MerkleCapReport {
+    /// Digests added by publishing caps instead of roots, over all commitments.
+    pub cap_digests: usize,
+    /// Digests removed from opening paths, over all queries.
+    pub path_digests_saved: usize,
+}
+
+impl MerkleCapReport {
+    /// Net change in proof size, in digests. Negative when the cap shrinks the proof.
+    #[must_use]
+    pub const fn net_digests(&self) -> isize {
+        self.cap_digests as isize - self.path_digests_saved as isize
+    }
+}
+
+/// Merkle cap higher than a committed tree.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "Merkle cap of height {cap_height} exceeds the height {tree_height} of commitment {commitment}"
+)]
+pub struct CapHeightTooLarge {
+    /// Index of the commitment: 0 for the initial one, `r + 1` for the one of round `r`.
+    pub commitment: usize,
+    /// Cap height of the MMCS making the commitment.
+    pub cap_height: usize,
+    /// Number of levels of the committed tree.
+    pub tree_height: usize,
+}
+
+/// Invalid combination of protocol parameters.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A round folds no variable.
+    #[error("round {round} folds no variable")]
+    ZeroFoldingFactor { round: usize },
+
+    /// The first round folds more variables than the polynomial has.
+    #[error("folding factor {folding_factor} exceeds the {num_variables} variables")]
+    FoldingFactorTooLarge {
+        folding_factor: usize,
+        num_variables: usize,
+    },
+
+    /// The initial domain shrinks faster than the first round folds.
+    #[error(
+        "initial domain reduction factor {reduction_factor} exceeds the first folding factor \
+         {folding_factor}"
+    )]
+    ReductionFactorTooLarge {
+        reduction_factor: usize,
+        folding_factor: usize,
+    },
+
+    /// The initial evaluation domain has no two-adic subgroup of its size.
+    #[error(
+        "domain of size 2^{log_domain_size} exceeds the two-adicity {two_adicity} of the field"
+    )]
+    DomainTooLarge {
+        log_domain_size: usize,
+        two_adicity: usize,
+    },
+
+    /// The extension field cannot reach the security level.
+    #[error(transparent)]
+    ExtensionFieldTooSmall(#[from] ExtensionFieldTooSmall),
+}
+
+/// Fallible construction of [`WhirConfig`].
+pub trait WhirConfigTryNew<MT>: Sized {
+    /// Checks `params` for a polynomial over `num_variables` variables, then builds the
+    /// configuration.
+    ///
+    /// `WhirConfig::new` panics, or builds a configuration the prover rejects, on the
+    /// combinations reported here.
+    ///
+    /// # Errors
+    /// Returns the first invalid parameter found.
+    fn try_new(num_variables: usize, params: ProtocolParameters<MT>) -> Result<Self, ConfigError>;
+}
+
+impl<EF, F, MT, Challenger> WhirConfigTryNew<MT> for WhirConfig<EF, F, MT, Challenger>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    MT: Mmcs<F> + Clone,
+{
+    fn try_new(num_variables: usize, params: ProtocolParameters<MT>) -> Result<Self, ConfigError> {
+        if let Some(round) = (0..2).find(|&round| params.folding_factor.at_round(round) == 0) {
+            return Err(ConfigError::ZeroFoldingFactor { round });
+        }
+
+        let folding_factor = params.folding_factor.at_round(0);
+        if folding_factor > num_variables {
+            return Err(ConfigError::FoldingFactorTooLarge {
+                folding_factor,
+                num_variables,
+            });
+        }
+        if params.rs_domain_initial_reduction_factor > folding_factor {
+            return Err(ConfigError::ReductionFactorTooLarge {
+                reduction_factor: params.rs_domain_initial_reduction_factor,
+                folding_factor,
+            });
+        }
+
+        let log_domain_size = num_variables + params.starting_log_inv_rate;
+        if log_domain_size > F::TWO_ADICITY {
+            return Err(ConfigError::DomainTooLarge {
+                log_domain_size,
+                two_adicity: F::TWO_ADICITY,
+            });
+        }
+        check_extension_field::<F, EF>(
+            params.security_level,
+            params.soundness_type,
+            log_domain_size,
+        )?;
+
+        Ok(Self::new(num_variables, params))
+    }
+}
+
+/// Extra methods on [`WhirConfig`].
+pub trait WhirConfigExt<F: Field> {
+    /// Deterministic digest over every parameter the prover and the verifier must agree on.
+    ///
+    /// It covers the global parameters, the parameters of every round, the orders of the base
+    /// and extension fields, and the MMCS. Two configurations with the same fingerprint run
+    /// exactly the same protocol.
+    ///
+    /// The MMCS, and the one committing to the rounds, are each identified by their tree
+    /// arity, their Merkle cap height and their commitment to a fixed matrix, which depends on
+    /// their hash and compression but not on the compiler or the platform.
+    fn fingerprint(&self) -> ConfigFingerprint;
+
+    /// Proof-size tradeoff of the Merkle caps of the configured MMCS.
+    ///
+    /// Every commitment (the initial one and one per round) pays for the cap of the MMCS
+    /// making it, and every STIR query opened against it saves the `arity - 1` siblings of
+    /// each level the cap replaces.
+    ///
+    /// # Errors
+    /// Returns an error if a cap is higher than the tree it caps.
+    fn merkle_cap_report(&self) -> Result<MerkleCapReport, CapHeightTooLarge>;
+
+    /// Checks that the extension field supports the security level of the configuration.
+    ///
+    /// See [`crate::whir::presets`] for the bound.
+    fn check_extension_field(&self) -> Result<(), ExtensionFieldTooSmall>;
+}
+
+/// Fiat-Shamir domain separator of `config`, starting with its fingerprint.
+///
+/// A transcript set up from it absorbs the fingerprint before the initial commitment, so a
+/// prover and a verifier running different configurations diverge from the first challenge
+/// on. The patterns of the statement and of the proof are then added as usual.
+pub fn domain_separator<EF, F, MT, Challenger>(
+    config: &WhirConfig<EF, F, MT, Challenger>,
+) -> DomainSeparator<EF, F>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Serialize,
+    RoundCommitment<F, MT>: Serialize,
+{
+    let fingerprint = config.fingerprint();
+    DomainSeparator::new(fingerprint.iter().map(|&byte| F::from_u8(byte)).collect())
+}
+
+impl<EF, F, MT, Challenger> WhirConfigExt<F> for WhirConfig<EF, F, MT, Challenger>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Serialize,
+    RoundCommitment<F, MT>: Serialize,
+{
+    fn fingerprint(&self) -> ConfigFingerprint {
+        let mut bytes = Vec::new();
+        let mut push = |value: usize| bytes.extend_from_slice(&(value as u64).to_le_bytes());
+
+        push(self.num_variables);
+        push(self.security_level);
+        push(self.starting_log_inv_rate);
+        push(self.commitment_ood_samples);
+        push(self.starting_folding_pow_bits);
+        push(self.n_rounds());
+        for (round_index, round) in self.round_parameters.iter().enumerate() {
+            push(self.folding_factor.at_round(round_index));
+            push(round.num_variables);
+            push(round.folding_factor);
+            push(round.domain_size);
+            push(round.num_queries);
+            push(round.ood_samples);
+            push(round.pow_bits);
+            push(round.folding_pow_bits);
+        }
+        push(self.folding_factor.at_round(self.n_rounds()));
+        push(self.final_queries);
+        push(self.final_pow_bits);
+        push(self.final_sumcheck_rounds);
+        push(self.final_folding_pow_bits);
+
+        push(match self.soundness_type {
+            SecurityAssumption::UniqueDecoding => 0,
+            SecurityAssumption::JohnsonBound => 1,
+            SecurityAssumption::CapacityBound => 2,
+        });
+        push(self.mmcs.arity());
+        push(self.mmcs.cap_height());
+        push(self.mmcs.round_mmcs().arity());
+        push(self.mmcs.round_mmcs().cap_height());
+
+        let (mmcs_commitment, _) = self.mmcs.commit_matrix(probe_matrix::<F>(&self.mmcs, 1));
+        let (round_mmcs_commitment, _) = self
+            .mmcs
+            .round_mmcs()
+            .commit_matrix(probe_matrix::<F>(self.mmcs.round_mmcs(), 1));
+
+        // Commitments are encoded with postcard, whose serde encoding is canonical: fields
+        // serialize their canonical representatives. Variable-length identifiers are
+        // length-prefixed to keep the encoding injective.
+        let identifiers = [
+            F::order().to_bytes_le(),
+            EF::order().to_bytes_le(),
+            postcard::to_allocvec(&mmcs_commitment).expect("commitments serialize"),
+            postcard::to_allocvec(&round_mmcs_commitment).expect("commitments serialize"),
+        ];
+        for identifier in identifiers {
+            bytes.extend_from_slice(&(identifier.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&identifier);
+        }
+
+        Keccak256Hash.hash_iter(bytes)
+    }
+
+    fn merkle_cap_report(&self) -> Result<MerkleCapReport, CapHeightTooLarge> {
+        let mut report = MerkleCapReport {
+            cap_digests: 0,
+            path_digests_saved: 0,
+        };
+
+        // The queries of a round open the commitment of the previous one, and the final
+        // queries the last commitment: each commitment is opened by exactly one query phase.
+        let final_round_config = self.final_round_config();
+        let phases = self.round_parameters.iter().chain([&final_round_config]);
+        for (commitment, round) in phases.enumerate() {
+            let shape: &dyn MerkleShape = if commitment == 0 {
+                &self.mmcs
+            } else {
+                self.mmcs.round_mmcs()
+            };
+            let cap_height = shape.cap_height();
+            let tree_height = shape.tree_height(round.domain_size >> round.folding_factor);
+            if cap_height > tree_height {
+                return Err(CapHeightTooLarge {
+                    commitment,
+                    cap_height,
+                    tree_height,
+                });
+            }
+
+            report.cap_digests += shape.arity().pow(cap_height as u32) - 1;
+            report.path_digests_saved += round.num_queries * (shape.arity() - 1) * cap_height;
+        }
+        Ok(report)
+    }
+
+    fn check_extension_field(&self) -> Result<(), ExtensionFieldTooSmall> {
+        check_extension_field::<F, EF>(
+            self.security_level,
+            self.soundness_type,
+            self.num_variables + self.starting_log_inv_rate,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::{DuplexChallenger, FieldChallenger};
+    use p3_field::extension::BinomialExtensionField;
+    use p3_koala_bear::KoalaBear;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+    use crate::{
+        parameters::FoldingFactor,
+        whir::{mmcs::SplitMmcs, presets::SecurityPreset},
+    };
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+    type MyMmcs = MerkleTreeMmcs<F, F, MyHash, MyCompress, 2, 8>;
+
+    fn make_params(security_level: usize, folding_factor: usize) -> ProtocolParameters<MyMmcs> {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        ProtocolParameters {
+            security_level,
+            pow_bits: 0,
+            rs_domain_initial_reduction_factor: 1,
+            folding_factor: FoldingFactor::Constant(folding_factor),
+            mmcs: MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0),
+            soundness_type: SecurityAssumption::CapacityBound,
+            starting_log_inv_rate: 1,
+        }
+    }
+
+    fn fingerprint_of(
+        num_variables: usize,
+        params: ProtocolParameters<MyMmcs>,
+    ) -> ConfigFingerprint {
+        WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, params).fingerprint()
+    }
+
+    #[test]
+    fn test_fingerprint_is_deterministic() {
+        assert_eq!(
+            fingerprint_of(10, make_params(32, 2)),
+            fingerprint_of(10, make_params(32, 2))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_binds_parameters() {
+        let reference = fingerprint_of(10, make_params(32, 2));
+
+        // Number of variables
+        assert_ne!(reference, fingerprint_of(12, make_params(32, 2)));
+        // Security level
+        assert_ne!(reference, fingerprint_of(10, make_params(64, 2)));
+        // Folding schedule
+        assert_ne!(reference, fingerprint_of(10, make_params(32, 5)));
+        // Round parameters that are not derived from the protocol parameters
+        let mut config = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, make_params(32, 2));
+        config.final_queries += 1;
+        assert_ne!(reference, config.fingerprint());
+    }
+
+    #[test]
+    fn test_domain_separator_binds_fingerprint() {
+        let challenger_perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(3));
+        let first_challenge = |config: &WhirConfig<EF, F, MyMmcs, MyChallenger>| -> EF {
+            let mut domainsep = domain_separator(config);
+            domainsep.commit_statement::<_, _, 8>(config);
+            domainsep.add_whir_proof::<_, _, 8>(config);
+            let mut challenger = MyChallenger::new(challenger_perm.clone());
+            domainsep.observe_domain_separator(&mut challenger);
+            challenger.sample_algebra_element()
+        };
+
+        // Same protocol parameters, but the MMCS hashes with another permutation: only the
+        // fingerprint tells the two transcripts apart.
+        let config = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, make_params(32, 2));
+        let mut other_params = make_params(32, 2);
+        let other_perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(2));
+        other_params.mmcs = MyMmcs::new(
+            MyHash::new(other_perm.clone()),
+            MyCompress::new(other_perm),
+            0,
+        );
+        let other = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, other_params);
+        assert_ne!(config.fingerprint(), other.fingerprint());
+
+        let same = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, make_params(32, 2));
+        assert_eq!(first_challenge(&config), first_challenge(&same));
+        assert_ne!(first_challenge(&config), first_challenge(&other));
+    }
+
+    #[test]
+    fn test_fingerprint_binds_cap_height() {
+        let reference = fingerprint_of(10, make_params(32, 2));
+        for cap_height in 1..=3 {
+            let mut params = make_params(32, 2);
+            let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+            params.mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), cap_height);
+            assert_ne!(reference, fingerprint_of(10, params));
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_binds_round_mmcs() {
+        type MySplitMmcs = SplitMmcs<MyMmcs, MyMmcs>;
+
+        let mut rng = SmallRng::seed_from_u64(2);
+        let other_perm = Perm::new_from_rng_128(&mut rng);
+        let other_mmcs = MyMmcs::new(
+            MyHash::new(other_perm.clone()),
+            MyCompress::new(other_perm),
+            0,
+        );
+        let fingerprint_with_rounds = |rounds: MyMmcs| {
+            let params = make_params(32, 2);
+            let params = ProtocolParameters {
+                security_level: params.security_level,
+                pow_bits: params.pow_bits,
+                rs_domain_initial_reduction_factor: params.rs_domain_initial_reduction_factor,
+                folding_factor: params.folding_factor,
+                mmcs: SplitMmcs::new(params.mmcs, rounds),
+                soundness_type: params.soundness_type,
+                starting_log_inv_rate: params.starting_log_inv_rate,
+            };
+            WhirConfig::<EF, F, MySplitMmcs, MyChallenger>::new(10, params).fingerprint()
+        };
+
+        assert_ne!(
+            fingerprint_with_rounds(make_params(32, 2).mmcs),
+            fingerprint_with_rounds(other_mmcs)
+        );
+    }
+
+    #[test]
+    fn test_merkle_cap_report() {
+        let config = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, make_params(32, 2));
+        let openings = config
+            .round_parameters
+            .iter()
+            .map(|round| round.num_queries)
+            .sum::<usize>()
+            + config.final_queries;
+
+        let report = config.merkle_cap_report().unwrap();
+        assert_eq!((report.cap_digests, report.path_digests_saved), (0, 0));
+
+        let with_cap = |cap_height| {
+            let mut params = make_params(32, 2);
+            let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+            params.mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), cap_height);
+            WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(10, params).merkle_cap_report()
+        };
+
+        let report = with_cap(2).unwrap();
+        assert_eq!(report.cap_digests, 3 * (config.n_rounds() + 1));
+        assert_eq!(report.path_digests_saved, 2 * openings);
+        assert!(report.net_digests() < 0);
+
+        // The cap cannot be higher than the smallest tree, the last one.
+        let final_round_config = config.final_round_config();
+        let smallest_tree =
+            (final_round_config.domain_size >> final_round_config.folding_factor).ilog2() as usize;
+        assert!(with_cap(smallest_tree).is_ok());
+        assert_eq!(
+            with_cap(smallest_tree + 1),
+            Err(CapHeightTooLarge {
+                commitment: config.n_rounds(),
+                cap_height: smallest_tree + 1,
+                tree_height: smallest_tree,
+            })
+        );
+    }
+
+    #[test]
+    fn test_check_extension_field() {
+        let config = |preset: SecurityPreset| {
+            let mmcs = make_params(32, 2).mmcs;
+            let params = preset.protocol_parameters(FoldingFactor::Constant(4), 1, mmcs);
+            WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(12, params)
+        };
+
+        // The quartic extension of BabyBear has 123 bits.
+        assert!(
+            config(SecurityPreset::Conjectured100)
+                .check_extension_field()
+                .is_ok()
+        );
+        let err = config(SecurityPreset::Conjectured128)
+            .check_extension_field()
+            .unwrap_err();
+        assert_eq!((err.required_bits, err.extension_bits), (141, 123));
+    }
+
+    #[test]
+    fn test_try_new() {
+        type Config = WhirConfig<EF, F, MyMmcs, MyChallenger>;
+        let with = |update: fn(&mut ProtocolParameters<MyMmcs>)| {
+            let mut params = make_params(32, 4);
+            update(&mut params);
+            params
+        };
+
+        let config = Config::try_new(10, make_params(32, 4)).unwrap();
+        assert_eq!(config.fingerprint(), fingerprint_of(10, make_params(32, 4)));
+
+        for (num_variables, params, expected) in [
+            (
+                10,
+                with(|params| params.folding_factor = FoldingFactor::Constant(0)),
+                ConfigError::ZeroFoldingFactor { round: 0 },
+            ),
+            (
+                10,
+                with(|params| {
+                    params.folding_factor = FoldingFactor::ConstantFromSecondRound(4, 0);
+                }),
+                ConfigError::ZeroFoldingFactor { round: 1 },
+            ),
+            (
+                3,
+                make_params(32, 4),
+                ConfigError::FoldingFactorTooLarge {
+                    folding_factor: 4,
+                    num_variables: 3,
+                },
+            ),
+            (
+                10,
+                with(|params| params.rs_domain_initial_reduction_factor = 5),
+                ConfigError::ReductionFactorTooLarge {
+                    reduction_factor: 5,
+                    folding_factor: 4,
+                },
+            ),
+            (
+                27,
+                make_params(32, 4),
+                ConfigError::DomainTooLarge {
+                    log_domain_size: 28,
+                    two_adicity: 27,
+                },
+            ),
+        ] {
+            assert_eq!(
+                Config::try_new(num_variables, params).unwrap_err(),
+                expected
+            );
+        }
+
+        assert!(matches!(
+            Config::try_new(10, make_params(128, 4)),
+            Err(ConfigError::ExtensionFieldTooSmall(_))
+        ));
+    }
+
+    #[test]
+    fn test_fingerprint_binds_fields() {
+        type KoalaEF = BinomialExtensionField<KoalaBear, 4>;
+        type KoalaPerm = p3_koala_bear::Poseidon2KoalaBear<16>;
+        type KoalaMmcs = MerkleTreeMmcs<
+            KoalaBear,
+            KoalaBear,
+            PaddingFreeSponge<KoalaPerm, 16, 8, 8>,
+            TruncatedPermutation<KoalaPerm, 2, 8, 16>,
+            2,
+            8,
+        >;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = KoalaPerm::new_from_rng_128(&mut rng);
+        let params = ProtocolParameters {
+            security_level: 32,
+            pow_bits: 0,
+            rs_domain_initial_reduction_factor: 1,
+            folding_factor: FoldingFactor::Constant(2),
+            mmcs: KoalaMmcs::new(
+                PaddingFreeSponge::new(perm.clone()),
+                TruncatedPermutation::new(perm),
+                0,
+            ),
+            soundness_type: SecurityAssumption::CapacityBound,
+            starting_log_inv_rate: 1,
+        };
+        let koala = WhirConfig::<
+            KoalaEF,
+            KoalaBear,
+            KoalaMmcs,
+            DuplexChallenger<KoalaBear, KoalaPerm, 16, 8>,
+        >::new(10, params)
+        .fingerprint();
+
+        assert_ne!(fingerprint_of(10, make_params(32, 2)), koala);
+    }
+
+    /// Protocol inputs and expected parameters, as recorded from the reference implementation.
+    #[derive(serde::Deserialize)]
+    struct ReferenceVectors {
+        reference_revision: Option<alloc::string::String>,
+        vectors: Vec<ReferenceVector>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ReferenceVector {
+        num_variables: usize,
+        security_level: usize,
+        pow_bits: usize,
+        first_folding_factor: usize,
+        folding_factor: usize,
+        starting_log_inv_rate: usize,
+        rs_domain_initial_reduction_factor: usize,
+        soundness_type: alloc::string::String,
+        expected: ReferenceParameters,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+    struct ReferenceParameters {
+        commitment_ood_samples: usize,
+        starting_folding_pow_bits: usize,
+        rounds: Vec<ReferenceRound>,
+        final_queries: usize,
+        final_pow_bits: usize,
+        final_sumcheck_rounds: usize,
+        final_folding_pow_bits: usize,
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq, Eq)]
+    struct ReferenceRound {
+        num_variables: usize,
+        domain_size: usize,
+        num_queries: usize,
+        ood_samples: usize,
+        pow_bits: usize,
+        folding_pow_bits: usize,
+    }
+
+    #[test]
+    #[ignore = "reference vectors have not been recorded yet"]
+    fn test_parameters_match_reference() {
+        let vectors: ReferenceVectors =
+            serde_json::from_str(include_str!("../../test-vectors/reference_parameters.json"))
+                .unwrap();
+        assert!(
+            vectors.reference_revision.is_some(),
+            "reference vectors must name the reference revision they were recorded from"
+        );
+        assert!(!vectors.vectors.is_empty(), "no reference vectors recorded");
+
+        for vector in vectors.vectors {
+            let mut params = make_params(vector.security_level, vector.folding_factor);
+            params.pow_bits = vector.pow_bits;
+            params.folding_factor = if vector.first_folding_factor == vector.folding_factor {
+                FoldingFactor::Constant(vector.folding_factor)
+            } else {
+                FoldingFactor::ConstantFromSecondRound(
+                    vector.first_folding_factor,
+                    vector.folding_factor,
+                )
+            };
+            params.starting_log_inv_rate = vector.starting_log_inv_rate;
+            params.rs_domain_initial_reduction_factor = vector.rs_domain_initial_reduction_factor;
+            params.soundness_type = vector.soundness_type.parse().unwrap();
+
+            let config =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(vector.num_variables, params);
+            let actual = ReferenceParameters {
+                commitment_ood_samples: config.commitment_ood_samples,
+                starting_folding_pow_bits: config.starting_folding_pow_bits,
+                rounds: config
+                    .round_parameters
+                    .iter()
+                    .map(|round| ReferenceRound {
+                        num_variables: round.num_variables,
+                        domain_size: round.domain_size,
+                        num_queries: round.num_queries,
+                        ood_samples: round.ood_samples,
+                        pow_bits: round.pow_bits,
+                        folding_pow_bits: round.folding_pow_bits,
+                    })
+                    .collect(),
+                final_queries: config.final_queries,
+                final_pow_bits: config.final_pow_bits,
+                final_sumcheck_rounds: config.final_sumcheck_rounds,
+                final_folding_pow_bits: config.final_folding_pow_bits,
+            };
+
+            assert_eq!(
+                actual, vector.expected,
+                "parameters drifted from the reference for {} variables",
+                vector.num_variables
+            );
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! Serializable description of a WHIR configuration.
+//!
+//! Services which prove and verify separately must build identical configurations. A
+//! [`ConfigDescription`] holds every protocol parameter, and the number of variables, in a form
+//! any serde format can carry, e.g. JSON or TOML:
+//!
+//! ```toml
+//! mmcs = "poseidon2-koalabear"
+//! num_variables = 20
+//! security_level = 100
+//! pow_bits = 16
+//! folding_factor = { ConstantFromSecondRound = [6, 4] }
+//! soundness_type = "CapacityBound"
+//! starting_log_inv_rate = 1
+//! rs_domain_initial_reduction_factor = 3
+//! ```
+//!
+//! Hash functions and MMCS instances cannot be serialized: the description names its MMCS by
+//! an identifier, resolved in an [`MmcsRegistry`] each service fills with the same instances.
+
+use alloc::{collections::BTreeMap, string::String};
+
+use p3_commit::Mmcs;
+use p3_field::{ExtensionField, TwoAdicField};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption, WhirConfig},
+    whir::config::{ConfigError, WhirConfigTryNew},
+};
+
+/// Serde mirror of [`FoldingFactor`].
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "FoldingFactor")]
+enum FoldingFactorDef {
+    Constant(usize),
+    ConstantFromSecondRound(usize, usize),
+}
+
+/// Serde mirror of [`SecurityAssumption`].
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SecurityAssumption")]
+enum SecurityAssumptionDef {
+    UniqueDecoding,
+    JohnsonBound,
+    CapacityBound,
+}
+
+/// Error returned when rebuilding a configuration from its description.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DescriptionError {
+    /// No MMCS is registered under the identifier of the description.
+    #[error("no MMCS registered as {0:?}")]
+    UnknownMmcs(String),
+
+    /// The parameters of the description are invalid.
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+}
+
+/// MMCS instances referenced by identifier from a [`ConfigDescription`].
+#[derive(Debug, Clone)]
+pub struct MmcsRegistry<MT> {
+    /// Instances, by identifier.
+    entries: BTreeMap<String, MT>,
+}
+
+impl<MT> Default for MmcsRegistry<MT> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<MT> MmcsRegistry<MT> {
+    /// Creates an empty registry.
+    pub const fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Registers `mmcs` as `id`, returning the instance it replaces, if any.
+    pub fn register(&mut self, id: impl Into<String>, mmcs: MT) -> Option<MT> {
+        self.entries.insert(id.into(), mmcs)
+    }
+
+    /// Registers `mmcs` as `id`.
+    #[must_use]
+    pub fn with(mut self, id: impl Into<String>, mmcs: MT) -> Self {
+        self.register(id, mmcs);
+        self
+    }
+
+    /// Returns the instance registered as `id`.
+    pub fn get(&self, id: &str) -> Option<&MT> {
+        self.entries.get(id)
+    }
+}
+
+/// Protocol parameters and number of variables of a WHIR configuration, with the MMCS
+/// referenced by identifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigDescription {
+    /// Identifier of the MMCS in the [`MmcsRegistry`].
+    pub mmcs: String,
+    /// Number of variables of the committed polynomials.
+    pub num_variables: usize,
+    /// Target security level, in bits.
+    pub security_level: usize,
+    /// Maximum PoW per round, in bits.
+    pub pow_bits: usize,
+    /// Number of variables folded per round.
+    #[serde(with = "FoldingFactorDef")]
+    pub folding_factor: FoldingFactor,
+    /// Soundness assumption the security level holds under.
+    #[serde(with = "SecurityAssumptionDef")]
+    pub soundness_type: SecurityAssumption,
+    /// Log inverse rate of the initial code.
+    pub starting_log_inv_rate: usize,
+    /// Log factor by which the first round shrinks the evaluation domain.
+    pub rs_domain_initial_reduction_factor: usize,
+}
+
+impl ConfigDescription {
+    /// Describes `params` over `num_variables` variables, with its MMCS registered as `mmcs`.
+    pub fn new<MT>(
+        mmcs: impl Into<String>,
+        num_variables: usize,
+        params: &ProtocolParameters<MT>,
+    ) -> Self {
+        Self {
+            mmcs: mmcs.into(),
+            num_variables,
+            security_level: params.security_level,
+            pow_bits: params.pow_bits,
+            folding_factor: params.folding_factor,
+            soundness_type: params.soundness_type,
+            starting_log_inv_rate: params.starting_log_inv_rate,
+            rs_domain_initial_reduction_factor: params.rs_domain_initial_reduction_factor,
+        }
+    }
+
+    /// Protocol parameters of the description, with the MMCS taken from `registry`.
+    ///
+    /// # Errors
+    /// Returns [`DescriptionError::UnknownMmcs`] if the MMCS is not registered.
+    pub fn protocol_parameters<MT: Clone>(
+        &self,
+        registry: &MmcsRegistry<MT>,
+    ) -> Result<ProtocolParameters<MT>, DescriptionError> {
+        let mmcs = registry
+            .get(&self.mmcs)
+            .ok_or_else(|| DescriptionError::UnknownMmcs(self.mmcs.clone()))?;
+        Ok(ProtocolParameters {
+            security_level: self.security_level,
+            pow_bits: self.pow_bits,
+            folding_factor: self.folding_factor,
+            mmcs: mmcs.clone(),
+            soundness_type: self.soundness_type,
+            starting_log_inv_rate: self.starting_log_inv_rate,
+            rs_domain_initial_reduction_factor: self.rs_domain_initial_reduction_factor,
+        })
+    }
+
+    /// Rebuilds the configuration, with the MMCS taken from `registry`.
+    ///
+    /// # Errors
+    /// Returns an error if the MMCS is not registered or if the parameters are invalid.
+    pub fn build<EF, F, MT, Challenger>(
+        &self,
+        registry: &MmcsRegistry<MT>,
+    ) -> Result<WhirConfig<EF, F, MT, Challenger>, DescriptionError>
+    where
+        F: TwoAdicField,
+        EF: ExtensionField<F> + TwoAdicField,
+        MT: Mmcs<F> + Clone,
+    {
+        let params = self.protocol_parameters(registry)?;
+        Ok(WhirConfig::try_new(self.num_variables, params)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+    use crate::whir::config::WhirConfigExt;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+    type MyMmcs = MerkleTreeMmcs<F, F, MyHash, MyCompress, 2, 8>;
+    type MyConfig = WhirConfig<EF, F, MyMmcs, MyChallenger>;
+
+    fn mmcs(seed: u64) -> MyMmcs {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(seed));
+        MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0)
+    }
+
+    fn registry() -> MmcsRegistry<MyMmcs> {
+        MmcsRegistry::new()
+            .with("poseidon2-1", mmcs(1))
+            .with("poseidon2-2", mmcs(2))
+    }
+
+    fn params() -> ProtocolParameters<MyMmcs> {
+        ProtocolParameters {
+            security_level: 32,
+            pow_bits: 8,
+            folding_factor: FoldingFactor::ConstantFromSecondRound(4, 2),
+            mmcs: mmcs(1),
+            soundness_type: SecurityAssumption::JohnsonBound,
+            starting_log_inv_rate: 2,
+            rs_domain_initial_reduction_factor: 2,
+        }
+    }
+
+    #[test]
+    fn test_description_round_trip() {
+        let description = ConfigDescription::new("poseidon2-1", 12, &params());
+
+        let json = serde_json::to_string(&description).unwrap();
+        let decoded: ConfigDescription = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, description);
+
+        // Both services rebuild the configuration of the original parameters.
+        let config: MyConfig = decoded.build(&registry()).unwrap();
+        assert_eq!(
+            config.fingerprint(),
+            MyConfig::new(12, params()).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_description_from_json() {
+        let json = r#"{
+            "mmcs": "poseidon2-2",
+            "num_variables": 10,
+            "security_level": 32,
+            "pow_bits": 0,
+            "folding_factor": { "Constant": 2 },
+            "soundness_type": "CapacityBound",
+            "starting_log_inv_rate": 1,
+            "rs_domain_initial_reduction_factor": 1
+        }"#;
+        let description: ConfigDescription = serde_json::from_str(json).unwrap();
+
+        let params = description.protocol_parameters(&registry()).unwrap();
+        assert_eq!(params.folding_factor, FoldingFactor::Constant(2));
+        assert_eq!(params.soundness_type, SecurityAssumption::CapacityBound);
+
+        // The MMCS instance is part of the configuration.
+        let config: MyConfig = description.build(&registry()).unwrap();
+        let mut other = description;
+        other.mmcs = "poseidon2-1".into();
+        let other: MyConfig = other.build(&registry()).unwrap();
+        assert_ne!(config.fingerprint(), other.fingerprint());
+
+        // Unknown fields are rejected rather than silently ignored.
+        let typo = json.replace("pow_bits", "pow_bit");
+        assert!(serde_json::from_str::<ConfigDescription>(&typo).is_err());
+    }
+
+    #[test]
+    fn test_description_from_toml_example() {
+        // The TOML example of the module documentation.
+        let source = include_str!("description.rs");
+        let start = source.find("//! ```toml\n").unwrap();
+        let example: String = source[start..]
+            .lines()
+            .skip(1)
+            .take_while(|line| *line != "//! ```")
+            .map(|line| format!("{}\n", line.trim_start_matches("//!").trim_start()))
+            .collect();
+        let description: ConfigDescription = toml::from_str(&example).unwrap();
+
+        assert_eq!(description.mmcs, "poseidon2-koalabear");
+        assert_eq!(description.num_variables, 20);
+        assert_eq!(
+            description.folding_factor,
+            FoldingFactor::ConstantFromSecondRound(6, 4)
+        );
+        assert_eq!(
+            description.soundness_type,
+            SecurityAssumption::CapacityBound
+        );
+
+        let registry = registry().with("poseidon2-koalabear", mmcs(1));
+        let config: MyConfig = description.build(&registry).unwrap();
+        assert_eq!(config.security_level, 100);
+
+        // TOML round trip.
+        let encoded = toml::to_string(&description).unwrap();
+        assert_eq!(
+            toml::from_str::<ConfigDescription>(&encoded).unwrap(),
+            description
+        );
+    }
+
+    #[test]
+    fn test_description_errors() {
+        let mut description = ConfigDescription::new("keccak", 12, &params());
+        assert_eq!(
+            description
+                .build::<EF, F, _, MyChallenger>(&registry())
+                .unwrap_err(),
+            DescriptionError::UnknownMmcs("keccak".into())
+        );
+
+        description.mmcs = "poseidon2-1".into();
+        description.num_variables = 3;
+        assert!(matches!(
+            description.build::<EF, F, _, MyChallenger>(&registry()),
+            Err(DescriptionError::Config(
+                ConfigError::FoldingFactorTooLarge { .. }
+            ))
+        ));
+    }
+}
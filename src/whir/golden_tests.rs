@@ -0,0 +1,256 @@
+//! Golden tests pinning the exact bytes of proofs produced for fixed seeds and configurations.
+//!
+//! Any change to the transcript (order of observations, query sampling, proof layout...)
+//! changes the snapshots. When such a change is intended, regenerate them with the ignored
+//! test
+//!
+//! ```text
+//! cargo test --release update_golden_proof_snapshots -- --ignored
+//! ```
+//!
+//! and commit the updated `test-vectors/proof_snapshots.json`.
+
+extern crate std;
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+use std::fs;
+
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{DuplexChallenger, FieldChallenger, HashChallenger, SerializingChallenger32};
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::{Field, extension::BinomialExtensionField};
+use p3_keccak::{Keccak256Hash, KeccakF};
+use p3_koala_bear::KoalaBear;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{
+    CompressionFunctionFromHasher, CryptographicHasher, PaddingFreeSponge, SerializingHasher,
+    TruncatedPermutation,
+};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::writer::CommitmentWriter, config::domain_separator, proof::WhirProof,
+        prover::Prover,
+    },
+};
+
+const SNAPSHOTS_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/test-vectors/proof_snapshots.json"
+);
+
+/// Seeds of the random polynomial and evaluation points.
+const SEEDS: [u64; 3] = [0, 1, 2];
+
+/// `(num_variables, folding_factor)` of each snapshotted configuration.
+const SHAPES: [(usize, usize); 2] = [(10, 2), (12, 4)];
+
+/// Number of extension elements sampled from the prover challenger once the proof is complete.
+const CHECKPOINT_CHALLENGES: usize = 4;
+
+/// Fingerprint of a proof and of the transcript state it leaves behind.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+struct Snapshot {
+    /// Keccak-256 of the bincode serialization of the proof.
+    proof_keccak256: String,
+    /// Keccak-256 of the bincode serialization of the challenges sampled after the proof.
+    challenger_checkpoint_keccak256: String,
+}
+
+impl Snapshot {
+    fn new(proof: &impl Serialize, checkpoint: &impl Serialize) -> Self {
+        Self {
+            proof_keccak256: keccak256_hex(proof),
+            challenger_checkpoint_keccak256: keccak256_hex(checkpoint),
+        }
+    }
+}
+
+/// Hex encoding of the Keccak-256 digest of the bincode serialization of `value`.
+fn keccak256_hex(value: &impl Serialize) -> String {
+    let bytes = bincode::serialize(value).unwrap();
+    let digest = Keccak256Hash.hash_iter(bytes);
+    digest.iter().fold(String::new(), |mut hex, byte| {
+        write!(hex, "{byte:02x}").unwrap();
+        hex
+    })
+}
+
+fn make_protocol_parameters<MT>(folding_factor: usize, mmcs: MT) -> ProtocolParameters<MT> {
+    ProtocolParameters {
+        security_level: 32,
+        pow_bits: 0,
+        rs_domain_initial_reduction_factor: 1,
+        folding_factor: FoldingFactor::Constant(folding_factor),
+        mmcs,
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+    }
+}
+
+fn poseidon2_snapshot(seed: u64, num_variables: usize, folding_factor: usize) -> Snapshot {
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+    type PackedF = <F as Field>::Packing;
+    type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
+
+    // The permutation is fixed, only the witness depends on the seed.
+    let mut perm_rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut perm_rng);
+    let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()), 0);
+
+    let whir_params = make_protocol_parameters(folding_factor, mmcs);
+    let params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+    for _ in 0..2 {
+        let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+    }
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    domainsep.add_whir_proof::<_, _, 8>(&params);
+    let mut challenger = MyChallenger::new(perm);
+    domainsep.observe_domain_separator(&mut challenger);
+
+    let dft = Radix2DFTSmallBatch::<F>::default();
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &statement.poly)
+        .unwrap();
+    let mut proof =
+        WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut proof,
+            &mut challenger,
+            &mut statement,
+            root,
+            &prover_data,
+        )
+        .unwrap();
+
+    let checkpoint: Vec<EF> = (0..CHECKPOINT_CHALLENGES)
+        .map(|_| challenger.sample_algebra_element())
+        .collect();
+    Snapshot::new(&proof, &checkpoint)
+}
+
+fn keccak_snapshot(seed: u64, num_variables: usize, folding_factor: usize) -> Snapshot {
+    type F = KoalaBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
+    type KeccakFieldHash = SerializingHasher<U64Hash>;
+    type KeccakCompress = CompressionFunctionFromHasher<U64Hash, 2, 4>;
+    type KeccakChallenger = SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>>;
+    type MyMmcs = MerkleTreeMmcs<F, u64, KeccakFieldHash, KeccakCompress, 2, 4>;
+
+    let u64_hash = U64Hash::new(KeccakF {});
+    let mmcs = MyMmcs::new(
+        KeccakFieldHash::new(u64_hash),
+        KeccakCompress::new(u64_hash),
+        0,
+    );
+
+    let whir_params = make_protocol_parameters(folding_factor, mmcs);
+    let params =
+        WhirConfig::<EF, F, MyMmcs, KeccakChallenger>::new(num_variables, whir_params.clone());
+
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+    for _ in 0..2 {
+        let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+    }
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 4>(&params);
+    domainsep.add_whir_proof::<_, _, 4>(&params);
+    let mut challenger = KeccakChallenger::new(HashChallenger::new(vec![], Keccak256Hash {}));
+    domainsep.observe_domain_separator(&mut challenger);
+
+    let dft = Radix2DFTSmallBatch::<F>::default();
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &statement.poly)
+        .unwrap();
+    let mut proof =
+        WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut proof,
+            &mut challenger,
+            &mut statement,
+            root,
+            &prover_data,
+        )
+        .unwrap();
+
+    let checkpoint: Vec<EF> = (0..CHECKPOINT_CHALLENGES)
+        .map(|_| challenger.sample_algebra_element())
+        .collect();
+    Snapshot::new(&proof, &checkpoint)
+}
+
+/// Snapshots of every seed and configuration, by name.
+fn proof_snapshots() -> BTreeMap<String, Snapshot> {
+    let mut snapshots = BTreeMap::new();
+    for seed in SEEDS {
+        for (num_variables, folding_factor) in SHAPES {
+            let shape = format!("nv{num_variables}_k{folding_factor}_seed{seed}");
+            snapshots.insert(
+                format!("babybear_poseidon2_{shape}"),
+                poseidon2_snapshot(seed, num_variables, folding_factor),
+            );
+            snapshots.insert(
+                format!("koalabear_keccak_{shape}"),
+                keccak_snapshot(seed, num_variables, folding_factor),
+            );
+        }
+    }
+    snapshots
+}
+
+#[test]
+fn test_golden_proof_snapshots() {
+    let actual = proof_snapshots();
+    let stored: BTreeMap<String, Snapshot> =
+        serde_json::from_str(&fs::read_to_string(SNAPSHOTS_PATH).unwrap()).unwrap();
+
+    let mismatches: Vec<String> = actual
+        .iter()
+        .filter(|(name, snapshot)| stored.get(*name) != Some(*snapshot))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    assert!(
+        mismatches.is_empty(),
+        "proof snapshots differ or are missing for {mismatches:?}; if the transcript change \
+         is intended, run `cargo test --release update_golden_proof_snapshots -- --ignored`"
+    );
+}
+
+#[test]
+#[ignore = "rewrites test-vectors/proof_snapshots.json"]
+fn update_golden_proof_snapshots() {
+    let json = serde_json::to_string_pretty(&proof_snapshots()).unwrap();
+    fs::write(SNAPSHOTS_PATH, json + "\n").unwrap();
+}
@@ -0,0 +1,242 @@
+//! Selection of the MMCS committing to each round.
+//!
+//! The initial commitment is made with the `mmcs` of the configuration, over base field
+//! leaves. The round commitments are made over extension field leaves, with an
+//! [`ExtensionMmcs`](p3_commit::ExtensionMmcs) wrapping the MMCS returned by
+//! [`WhirMmcs::round_mmcs`].
+//!
+//! A plain [`MerkleTreeMmcs`] commits to every round itself. A [`SplitMmcs`] commits to the
+//! rounds with another MMCS, possibly of another type: e.g. Keccak for the initial commitment,
+//! which is cheap to verify natively, and Poseidon2 for the rounds, which are cheap to verify
+//! in a recursive circuit.
+
+use alloc::vec::Vec;
+
+use p3_commit::{BatchOpening, BatchOpeningRef, Mmcs};
+use p3_field::Field;
+use p3_matrix::{Dimensions, Matrix, dense::RowMajorMatrix};
+use p3_merkle_tree::MerkleTreeMmcs;
+use serde::Serialize;
+
+/// Shape of the Merkle trees built by an MMCS, which determines the size of its proofs.
+pub trait MerkleShape {
+    /// Number of children of every inner node.
+    fn arity(&self) -> usize;
+
+    /// Height of the Merkle cap: commitments publish the `arity^h` nodes at depth `h` of
+    /// each tree instead of its root.
+    fn cap_height(&self) -> usize;
+
+    /// Number of levels of a tree over `num_leaves` leaves, rounded up.
+    fn tree_height(&self, num_leaves: usize) -> usize {
+        let arity = self.arity();
+        let mut height = 0;
+        let mut width = 1;
+        while width < num_leaves {
+            width *= arity;
+            height += 1;
+        }
+        height
+    }
+
+    /// Maximum number of digests in the opening path of a tree over `num_leaves` leaves.
+    ///
+    /// Every level below the cap contributes the `arity - 1` siblings of the opened node.
+    fn max_path_digests(&self, num_leaves: usize) -> usize {
+        (self.arity() - 1)
+            * self
+                .tree_height(num_leaves)
+                .saturating_sub(self.cap_height())
+    }
+}
+
+/// Single-column matrix whose tree has `levels` levels below the cap of `shape`.
+///
+/// Committing to it exercises both the hash and the compression of the MMCS; the rows are
+/// fixed, so the commitment only depends on the MMCS.
+pub(crate) fn probe_matrix<F: Field>(shape: &dyn MerkleShape, levels: usize) -> RowMajorMatrix<F> {
+    let height = shape.arity().pow((shape.cap_height() + levels) as u32);
+    RowMajorMatrix::new((0..height).map(|row| F::from_u8(row as u8)).collect(), 1)
+}
+
+/// Sizes, in bytes, of the serialized commitments and digests of an MMCS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SerializedSizes {
+    /// Size of a commitment, i.e. of its whole Merkle cap.
+    pub(crate) commitment: usize,
+    /// Size of one digest of an opening path.
+    pub(crate) digest: usize,
+}
+
+impl SerializedSizes {
+    /// Levels by which the longer probe path exceeds the shorter one.
+    const PROBE_LEVELS: usize = 3;
+
+    /// Measures the sizes of `mmcs` on probe trees, encoded with postcard.
+    ///
+    /// The digest size is the growth of an opening proof when its path gains
+    /// [`Self::PROBE_LEVELS`] levels, divided by the `arity - 1` digests each level adds: the
+    /// length prefix of the encoding cancels out, and the variable-length integers of postcard
+    /// are averaged over several digests.
+    pub(crate) fn of<F, M>(mmcs: &M) -> Self
+    where
+        F: Field,
+        M: Mmcs<F> + MerkleShape,
+        M::Commitment: Serialize,
+        M::Proof: Serialize,
+    {
+        let measure = |levels| {
+            let (commitment, prover_data) = mmcs.commit_matrix(probe_matrix::<F>(mmcs, levels));
+            let proof = mmcs.open_batch(0, &prover_data).opening_proof;
+            (
+                postcard::to_allocvec(&commitment)
+                    .expect("commitments serialize")
+                    .len(),
+                postcard::to_allocvec(&proof)
+                    .expect("proofs serialize")
+                    .len(),
+            )
+        };
+        let (commitment, short_proof) = measure(1);
+        let (_, long_proof) = measure(1 + Self::PROBE_LEVELS);
+        Self {
+            commitment,
+            digest: long_proof.saturating_sub(short_proof)
+                / (Self::PROBE_LEVELS * (mmcs.arity() - 1)),
+        }
+    }
+}
+
+/// MMCS used by WHIR, together with the MMCS committing to the rounds.
+pub trait WhirMmcs<F: Send + Sync + Clone>: Mmcs<F> + MerkleShape {
+    /// MMCS committing to the folded polynomial of every round.
+    type Round: Mmcs<F> + MerkleShape;
+
+    /// Returns the MMCS committing to the rounds.
+    fn round_mmcs(&self) -> &Self::Round;
+}
+
+/// MMCS committing to the rounds of a WHIR proof made with `MT`.
+pub type RoundMmcs<F, MT> = <MT as WhirMmcs<F>>::Round;
+
+/// Commitment to a round of a WHIR proof made with `MT`.
+pub type RoundCommitment<F, MT> = <RoundMmcs<F, MT> as Mmcs<F>>::Commitment;
+
+/// Opening proof of a round commitment of a WHIR proof made with `MT`.
+pub type RoundProof<F, MT> = <RoundMmcs<F, MT> as Mmcs<F>>::Proof;
+
+impl<P, PW, H, C, const N: usize, const DIGEST_ELEMS: usize> MerkleShape
+    for MerkleTreeMmcs<P, PW, H, C, N, DIGEST_ELEMS>
+{
+    fn arity(&self) -> usize {
+        N
+    }
+
+    fn cap_height(&self) -> usize {
+        Self::cap_height(self)
+    }
+}
+
+impl<F, P, PW, H, C, const N: usize, const DIGEST_ELEMS: usize> WhirMmcs<F>
+    for MerkleTreeMmcs<P, PW, H, C, N, DIGEST_ELEMS>
+where
+    F: Send + Sync + Clone,
+    Self: Mmcs<F>,
+{
+    type Round = Self;
+
+    fn round_mmcs(&self) -> &Self::Round {
+        self
+    }
+}
+
+/// MMCS committing to the initial polynomial with `Initial` and to the rounds with `Rounds`.
+///
+/// As an [`Mmcs`], it behaves exactly as `Initial`.
+#[derive(Debug, Clone)]
+pub struct SplitMmcs<Initial, Rounds> {
+    /// MMCS committing to the initial polynomial.
+    pub initial: Initial,
+    /// MMCS committing to the folded polynomial of every round.
+    pub rounds: Rounds,
+}
+
+impl<Initial, Rounds> SplitMmcs<Initial, Rounds> {
+    /// Commits to the initial polynomial with `initial` and to the rounds with `rounds`.
+    pub const fn new(initial: Initial, rounds: Rounds) -> Self {
+        Self { initial, rounds }
+    }
+}
+
+impl<F, Initial, Rounds> Mmcs<F> for SplitMmcs<Initial, Rounds>
+where
+    F: Send + Sync + Clone,
+    Initial: Mmcs<F>,
+    Rounds: Clone,
+{
+    type ProverData<M> = Initial::ProverData<M>;
+    type Commitment = Initial::Commitment;
+    type Proof = Initial::Proof;
+    type Error = Initial::Error;
+
+    fn commit<M: Matrix<F>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
+        self.initial.commit(inputs)
+    }
+
+    fn open_batch<M: Matrix<F>>(
+        &self,
+        index: usize,
+        prover_data: &Self::ProverData<M>,
+    ) -> BatchOpening<F, Self> {
+        let opening = self.initial.open_batch(index, prover_data);
+        BatchOpening {
+            opened_values: opening.opened_values,
+            opening_proof: opening.opening_proof,
+        }
+    }
+
+    fn get_matrices<'a, M: Matrix<F>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
+        self.initial.get_matrices(prover_data)
+    }
+
+    fn verify_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        index: usize,
+        batch_opening: BatchOpeningRef<'_, F, Self>,
+    ) -> Result<(), Self::Error> {
+        self.initial.verify_batch(
+            commit,
+            dimensions,
+            index,
+            BatchOpeningRef {
+                opened_values: batch_opening.opened_values,
+                opening_proof: batch_opening.opening_proof,
+            },
+        )
+    }
+}
+
+impl<Initial: MerkleShape, Rounds> MerkleShape for SplitMmcs<Initial, Rounds> {
+    fn arity(&self) -> usize {
+        self.initial.arity()
+    }
+
+    fn cap_height(&self) -> usize {
+        self.initial.cap_height()
+    }
+}
+
+impl<F, Initial, Rounds> WhirMmcs<F> for SplitMmcs<Initial, Rounds>
+where
+    F: Send + Sync + Clone,
+    Initial: Mmcs<F> + MerkleShape,
+    Rounds: Mmcs<F> + MerkleShape,
+{
+    type Round = Rounds;
+
+    fn round_mmcs(&self) -> &Self::Round {
+        &self.rounds
+    }
+}
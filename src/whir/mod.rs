@@ -1,5 +1,14 @@
 pub mod committer;
+pub mod config;
+pub mod description;
+#[cfg(test)]
+mod golden_tests;
+pub mod mmcs;
+pub mod optimizer;
+pub mod presets;
 pub mod proof;
+#[cfg(test)]
+mod proptests;
 pub mod prover;
 pub mod utils;
 pub mod verifier;
@@ -7,7 +16,8 @@ pub mod verifier;
 #[cfg(test)]
 mod test {
 
-    use alloc::vec;
+    use alloc::sync::Arc;
+    use core::sync::atomic::{AtomicBool, Ordering};
 
     use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
     use p3_challenger::{DuplexChallenger, FieldChallenger};
@@ -19,15 +29,25 @@ mod test {
     use rand::{RngExt, SeedableRng, rngs::SmallRng};
 
     use crate::{
-        fiat_shamir::domain_separator::DomainSeparator,
+        constraints::statement::EqStatement,
         parameters::{
             FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
         },
         whir::{
             committer::{reader::CommitmentReader, writer::CommitmentWriter},
-            proof::WhirProof,
-            prover::Prover,
-            verifier::Verifier,
+            config::domain_separator,
+            mmcs::SplitMmcs,
+            proof::{InitialClaims, WhirProof},
+            prover::{
+                Prover,
+                errors::ProverError,
+                pow::{Grinder, GrindingError},
+            },
+            verifier::{
+                Verifier,
+                diagnostics::{TranscriptOp, TranscriptRecorder},
+                errors::{VerifierError, VerifierPhase},
+            },
         },
     };
 
@@ -52,6 +72,7 @@ mod test {
         pow_bits: usize,
         rs_domain_initial_reduction_factor: usize,
         sumcheck_strategy: SumcheckStrategy,
+        cap_height: usize,
     ) {
         // Calculate polynomial size: 2^num_variables coefficients for multilinear polynomial
         let num_evaluations = 1 << num_variables;
@@ -66,7 +87,7 @@ mod test {
         let merkle_hash = MyHash::new(perm.clone());
         // Compression for leaf-to-parent hashing
         let merkle_compress = MyCompress::new(perm);
-        let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
+        let mmcs = MyMmcs::new(merkle_hash, merkle_compress, cap_height);
 
         // Configure WHIR protocol with all security and performance parameters
         let whir_params = ProtocolParameters {
@@ -97,7 +118,7 @@ mod test {
         let verifier_statement = statement.normalize();
 
         // Setup Fiat-Shamir transcript structure for non-interactive proof generation
-        let mut domainsep = DomainSeparator::new(vec![]);
+        let mut domainsep = domain_separator(&params);
         // Add statement commitment to transcript
         domainsep.commit_statement::<_, _, 8>(&params);
         // Add proof structure to transcript
@@ -118,9 +139,7 @@ mod test {
             WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
 
         // Commit to polynomial evaluations and generate cryptographic witness
-        let prover_data = committer
-            .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-            .unwrap();
+        let (root, prover_data) = committer.commit(&dft, &statement.poly).unwrap();
 
         // Initialize WHIR prover with the configured parameters
         let prover = Prover(&params);
@@ -131,8 +150,9 @@ mod test {
                 &dft,
                 &mut proof,
                 &mut prover_challenger,
-                &statement,
-                prover_data,
+                &mut statement,
+                root,
+                &prover_data,
             )
             .unwrap();
 
@@ -151,8 +171,9 @@ mod test {
         domainsep.observe_domain_separator(&mut verifier_challenger);
 
         // Parse and validate the polynomial commitment from proof data
-        let parsed_commitment =
-            commitment_reader.parse_commitment::<F, 8>(&proof, &mut verifier_challenger);
+        let parsed_commitment = commitment_reader
+            .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+            .unwrap();
 
         // Execute WHIR verification
         verifier
@@ -212,6 +233,7 @@ mod test {
                                         pow_bits,
                                         rs_domain_initial_reduction_factor,
                                         SumcheckStrategy::Svo,
+                                        0,
                                     );
                                     make_whir_things(
                                         num_variable,
@@ -221,6 +243,7 @@ mod test {
                                         pow_bits,
                                         rs_domain_initial_reduction_factor,
                                         SumcheckStrategy::Classic,
+                                        0,
                                     );
                                 }
                             }
@@ -229,17 +252,569 @@ mod test {
                 }
             }
         }
+
+        #[test]
+        fn test_whir_merkle_cap_end_to_end() {
+            for cap_height in 1..=3 {
+                for soundness_type in [
+                    SecurityAssumption::JohnsonBound,
+                    SecurityAssumption::CapacityBound,
+                ] {
+                    make_whir_things(
+                        10,
+                        FoldingFactor::Constant(2),
+                        2,
+                        soundness_type,
+                        0,
+                        1,
+                        SumcheckStrategy::Classic,
+                        cap_height,
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_whir_open_commitment_multiple_times() {
+            let num_variables = 8;
+            let folding_factor = FoldingFactor::Constant(2);
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor,
+                mmcs,
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            // Commit once, before any opening transcript exists.
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &polynomial)
+                .unwrap();
+            let statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+
+            // Open the same prover data several times, each at points chosen after the commitment.
+            for num_points in 1..=3 {
+                let mut statement = statement.clone();
+                for _ in 0..num_points {
+                    let point = Point::expand_from_univariate(rng.random(), num_variables);
+                    let _ = statement.evaluate(&point);
+                }
+                let verifier_statement = statement.normalize();
+
+                let mut proof = WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(
+                    &whir_params,
+                    num_variables,
+                );
+                let mut prover_challenger = challenger.clone();
+                domainsep.observe_domain_separator(&mut prover_challenger);
+                Prover(&params)
+                    .prove(
+                        &dft,
+                        &mut proof,
+                        &mut prover_challenger,
+                        &mut statement,
+                        root.clone(),
+                        &prover_data,
+                    )
+                    .unwrap();
+
+                let mut verifier_challenger = challenger.clone();
+                domainsep.observe_domain_separator(&mut verifier_challenger);
+                let parsed_commitment = CommitmentReader::new(&params)
+                    .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+                    .unwrap();
+
+                Verifier::new(&params)
+                    .verify(
+                        &proof,
+                        &mut verifier_challenger,
+                        &parsed_commitment,
+                        verifier_statement,
+                    )
+                    .unwrap();
+            }
+        }
+
+        #[test]
+        fn test_whir_cancellable_grinding() {
+            let num_variables = 8;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 10,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs,
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let mut params =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+            assert!(params.round_parameters[0].pow_bits > 0);
+            // The initial sumcheck grinds first, through the same grinder.
+            params.starting_folding_pow_bits = 10;
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+            let point = Point::expand_from_univariate(rng.random(), num_variables);
+            let _ = statement.evaluate(&point);
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let cancel = Arc::new(AtomicBool::new(false));
+            let grinder = Grinder::new()
+                .with_cancel_flag(cancel.clone())
+                .with_batch_size(1 << 4);
+            let prove = |grinder: &Grinder| {
+                let mut proof = WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(
+                    &whir_params,
+                    num_variables,
+                );
+                let mut prover_challenger = challenger.clone();
+                domainsep.observe_domain_separator(&mut prover_challenger);
+                Prover(&params)
+                    .prove_with_grinder(
+                        grinder,
+                        &dft,
+                        &mut proof,
+                        &mut prover_challenger,
+                        &mut statement.clone(),
+                        root.clone(),
+                        &prover_data,
+                    )
+                    .map(|()| proof)
+            };
+
+            // Small batches find witnesses the verifier accepts.
+            let proof = prove(&grinder).unwrap();
+            let mut verifier_challenger = challenger.clone();
+            domainsep.observe_domain_separator(&mut verifier_challenger);
+            let parsed_commitment = CommitmentReader::new(&params)
+                .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+                .unwrap();
+            Verifier::new(&params)
+                .verify(
+                    &proof,
+                    &mut verifier_challenger,
+                    &parsed_commitment,
+                    verifier_statement,
+                )
+                .unwrap();
+
+            // Once cancelled, the prover stops at the first proof-of-work.
+            cancel.store(true, Ordering::Relaxed);
+            assert!(matches!(
+                prove(&grinder),
+                Err(ProverError::SumcheckPow {
+                    round: 0,
+                    source: GrindingError::Cancelled { .. }
+                })
+            ));
+        }
+
+        #[test]
+        fn test_whir_self_describing_proof() {
+            let num_variables = 6;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs,
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+            for _ in 0..2 {
+                let point = Point::expand_from_univariate(rng.random(), num_variables);
+                let _ = statement.evaluate(&point);
+            }
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            // Prove, embedding the claims in the proof.
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let mut proof =
+                WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+            proof.claims = Some(InitialClaims::new(num_variables, &verifier_statement));
+            let mut prover_challenger = challenger.clone();
+            domainsep.observe_domain_separator(&mut prover_challenger);
+            Prover(&params)
+                .prove(
+                    &dft,
+                    &mut proof,
+                    &mut prover_challenger,
+                    &mut statement,
+                    root,
+                    &prover_data,
+                )
+                .unwrap();
+
+            let verify = |proof: &WhirProof<F, EF, MyMmcs>, statement: Option<EqStatement<EF>>| {
+                let mut verifier_challenger = challenger.clone();
+                domainsep.observe_domain_separator(&mut verifier_challenger);
+                let parsed_commitment = CommitmentReader::new(&params)
+                    .parse_commitment::<F, 8>(proof, &mut verifier_challenger)
+                    .unwrap();
+                let verifier = Verifier::new(&params);
+                match statement {
+                    Some(statement) => verifier.verify(
+                        proof,
+                        &mut verifier_challenger,
+                        &parsed_commitment,
+                        statement,
+                    ),
+                    None => verifier.verify_standalone(
+                        proof,
+                        &mut verifier_challenger,
+                        &parsed_commitment,
+                    ),
+                }
+            };
+
+            // The proof verifies on its own, and against the matching statement.
+            verify(&proof, None).unwrap();
+            verify(&proof, Some(verifier_statement.clone())).unwrap();
+
+            // A statement other than the embedded claims is rejected.
+            let mut other_statement = EqStatement::initialize(num_variables);
+            for (point, &eval) in verifier_statement.iter().take(1) {
+                other_statement.add_evaluated_constraint(point.clone(), eval);
+            }
+            assert!(matches!(
+                verify(&proof, Some(other_statement)),
+                Err(VerifierError::ClaimsMismatch { .. })
+            ));
+
+            // Claims over the wrong number of variables are rejected.
+            let mut wrong_arity = proof.clone();
+            wrong_arity.claims.as_mut().unwrap().num_variables += 1;
+            assert!(matches!(
+                verify(&wrong_arity, None),
+                Err(VerifierError::ClaimsMismatch { .. })
+            ));
+
+            // Without embedded claims, the proof cannot be verified standalone.
+            let mut without_claims = proof;
+            without_claims.claims = None;
+            assert!(matches!(
+                verify(&without_claims, None),
+                Err(VerifierError::MissingClaims)
+            ));
+            verify(&without_claims, Some(verifier_statement)).unwrap();
+        }
+
+        #[test]
+        fn test_whir_config_mismatch() {
+            let num_variables = 6;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs,
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+            let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let mut proof =
+                WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+            let mut prover_challenger = challenger.clone();
+            domainsep.observe_domain_separator(&mut prover_challenger);
+            Prover(&params)
+                .prove(
+                    &dft,
+                    &mut proof,
+                    &mut prover_challenger,
+                    &mut statement,
+                    root,
+                    &prover_data,
+                )
+                .unwrap();
+
+            // The verifier uses a configuration that differs only in the final number of queries.
+            let mut other_params =
+                WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params);
+            other_params.final_queries += 1;
+
+            let mut verifier_challenger = challenger;
+            domainsep.observe_domain_separator(&mut verifier_challenger);
+            let parsed_commitment = CommitmentReader::new(&other_params)
+                .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+                .unwrap();
+            let result = Verifier::new(&other_params).verify(
+                &proof,
+                &mut verifier_challenger,
+                &parsed_commitment,
+                verifier_statement,
+            );
+
+            assert!(matches!(result, Err(VerifierError::ConfigMismatch)));
+        }
+
+        #[test]
+        fn test_whir_split_mmcs() {
+            type MySplitMmcs = SplitMmcs<MyMmcs, MyMmcs>;
+
+            let num_variables = 8;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let initial_perm = Perm::new_from_rng_128(&mut rng);
+            let round_perm = Perm::new_from_rng_128(&mut rng);
+            let initial_mmcs = MyMmcs::new(
+                MyHash::new(initial_perm.clone()),
+                MyCompress::new(initial_perm),
+                0,
+            );
+            let round_mmcs = MyMmcs::new(
+                MyHash::new(round_perm.clone()),
+                MyCompress::new(round_perm),
+                0,
+            );
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs: SplitMmcs::new(initial_mmcs.clone(), round_mmcs),
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params = WhirConfig::<EF, F, MySplitMmcs, MyChallenger>::new(
+                num_variables,
+                whir_params.clone(),
+            );
+            assert!(params.n_rounds() > 0);
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+            let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let mut proof = WhirProof::<F, EF, MySplitMmcs>::from_protocol_parameters(
+                &whir_params,
+                num_variables,
+            );
+            let mut prover_challenger = challenger.clone();
+            domainsep.observe_domain_separator(&mut prover_challenger);
+            Prover(&params)
+                .prove(
+                    &dft,
+                    &mut proof,
+                    &mut prover_challenger,
+                    &mut statement,
+                    root,
+                    &prover_data,
+                )
+                .unwrap();
+
+            let verify = |params: &WhirConfig<EF, F, MySplitMmcs, MyChallenger>| {
+                let mut verifier_challenger = challenger.clone();
+                domainsep.observe_domain_separator(&mut verifier_challenger);
+                let parsed_commitment = CommitmentReader::new(params)
+                    .parse_commitment::<F, 8>(&proof, &mut verifier_challenger)
+                    .unwrap();
+                Verifier::new(params).verify(
+                    &proof,
+                    &mut verifier_challenger,
+                    &parsed_commitment,
+                    verifier_statement.clone(),
+                )
+            };
+
+            verify(&params).unwrap();
+
+            // A verifier committing to the rounds with the initial MMCS runs another protocol.
+            let mut same_mmcs_params = whir_params;
+            same_mmcs_params.mmcs = SplitMmcs::new(initial_mmcs.clone(), initial_mmcs);
+            let same_mmcs_params = WhirConfig::new(num_variables, same_mmcs_params);
+            assert!(matches!(
+                verify(&same_mmcs_params),
+                Err(VerifierError::ConfigMismatch)
+            ));
+        }
+
+        #[test]
+        fn test_whir_verifier_diagnostics() {
+            use p3_field::PrimeCharacteristicRing;
+
+            type RecordingChallenger = TranscriptRecorder<MyChallenger>;
+
+            let num_variables = 6;
+
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs,
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params = WhirConfig::<EF, F, MyMmcs, RecordingChallenger>::new(
+                num_variables,
+                whir_params.clone(),
+            );
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+            let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 8>(&params);
+            domainsep.add_whir_proof::<_, _, 8>(&params);
+            let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+            // Prove while recording the prover transcript.
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let mut proof =
+                WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+            let mut prover_challenger = RecordingChallenger::new(challenger.clone());
+            domainsep.observe_domain_separator(&mut prover_challenger);
+            Prover(&params)
+                .prove(
+                    &dft,
+                    &mut proof,
+                    &mut prover_challenger,
+                    &mut statement,
+                    root,
+                    &prover_data,
+                )
+                .unwrap();
+            let prover_trace = prover_challenger.into_events();
+
+            let diagnose = |proof: &WhirProof<F, EF, MyMmcs>| {
+                let mut verifier_challenger = RecordingChallenger::new(challenger.clone());
+                domainsep.observe_domain_separator(&mut verifier_challenger);
+                let parsed_commitment = CommitmentReader::new(&params)
+                    .parse_commitment::<F, 8>(proof, &mut verifier_challenger)
+                    .unwrap();
+                Verifier::new(&params).debug_verify(
+                    proof,
+                    &mut verifier_challenger,
+                    &parsed_commitment,
+                    verifier_statement.clone(),
+                    &prover_trace,
+                )
+            };
+
+            // The honest proof verifies with the recording challenger.
+            diagnose(&proof).unwrap();
+
+            // Tampering with an OOD answer of the first round is reported in that round, and the
+            // transcripts diverge exactly where the verifier observes the tampered answer.
+            let mut tampered = proof.clone();
+            tampered.rounds[0].ood_answers[0] += EF::ONE;
+            let diagnosis = diagnose(&tampered).unwrap_err();
+            assert_eq!(diagnosis.error.round(), Some(0));
+            let divergence = diagnosis.divergence.unwrap();
+            assert_eq!(divergence.prover.unwrap().op, TranscriptOp::Observe);
+            assert_eq!(divergence.verifier.unwrap().op, TranscriptOp::Observe);
+
+            // Dropping the final polynomial is reported as a malformed proof in the final phase.
+            let mut truncated = proof;
+            truncated.final_poly = None;
+            let diagnosis = diagnose(&truncated).unwrap_err();
+            assert_eq!(diagnosis.error.round(), Some(params.n_rounds()));
+            assert_eq!(diagnosis.error.phase(), Some(VerifierPhase::FinalPoly));
+        }
     }
 
     #[cfg(test)]
     mod keccak_tests {
         use alloc::vec;
 
+        use bincode::Options;
         use p3_challenger::{HashChallenger, SerializingChallenger32};
         use p3_dft::Radix2DFTSmallBatch;
         use p3_field::extension::BinomialExtensionField;
         use p3_keccak::{Keccak256Hash, KeccakF};
-        use p3_koala_bear::KoalaBear;
+        use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
         use p3_symmetric::{CompressionFunctionFromHasher, PaddingFreeSponge, SerializingHasher};
         use rand::{RngExt, SeedableRng, rngs::SmallRng};
 
@@ -253,15 +828,16 @@ mod test {
         // Keccak hash types producing [u64; 4] digests
         type U64Hash = PaddingFreeSponge<KeccakF, 25, 17, 4>;
         type KeccakFieldHash = SerializingHasher<U64Hash>;
-        type KeccakCompress = CompressionFunctionFromHasher<U64Hash, 2, 4>;
+        type KeccakCompress<const ARITY: usize> = CompressionFunctionFromHasher<U64Hash, ARITY, 4>;
 
         // Keccak challenger using byte-based HashChallenger
         type KeccakChallenger = SerializingChallenger32<F, HashChallenger<u8, Keccak256Hash, 32>>;
-        type MyMmcs = MerkleTreeMmcs<F, u64, KeccakFieldHash, KeccakCompress, 2, 4>;
+        type MyMmcs<const ARITY: usize> =
+            MerkleTreeMmcs<F, u64, KeccakFieldHash, KeccakCompress<ARITY>, ARITY, 4>;
 
-        /// Run a complete WHIR proof lifecycle with Keccak-based Merkle trees.
+        /// Run a complete WHIR proof lifecycle with `ARITY`-ary Keccak-based Merkle trees.
         #[allow(clippy::too_many_arguments)]
-        fn make_whir_things_keccak(
+        fn make_whir_things_keccak<const ARITY: usize>(
             num_variables: usize,
             folding_factor: FoldingFactor,
             num_points: usize,
@@ -269,6 +845,7 @@ mod test {
             pow_bits: usize,
             rs_domain_initial_reduction_factor: usize,
             sumcheck_strategy: SumcheckStrategy,
+            cap_height: usize,
         ) {
             let num_evaluations = 1 << num_variables;
 
@@ -276,7 +853,7 @@ mod test {
             let u64_hash = U64Hash::new(KeccakF {});
             let merkle_hash = KeccakFieldHash::new(u64_hash);
             let merkle_compress = KeccakCompress::new(u64_hash);
-            let mmcs = MyMmcs::new(merkle_hash, merkle_compress, 0);
+            let mmcs = MyMmcs::<ARITY>::new(merkle_hash, merkle_compress, cap_height);
 
             // Configure WHIR protocol with Keccak hashing
             let whir_params = ProtocolParameters {
@@ -289,7 +866,7 @@ mod test {
                 starting_log_inv_rate: 1,
             };
 
-            let params = WhirConfig::<EF, F, MyMmcs, KeccakChallenger>::new(
+            let params = WhirConfig::<EF, F, MyMmcs<ARITY>, KeccakChallenger>::new(
                 num_variables,
                 whir_params.clone(),
             );
@@ -309,7 +886,7 @@ mod test {
             let verifier_statement = statement.normalize();
 
             // Setup Fiat-Shamir transcript
-            let mut domainsep = DomainSeparator::new(vec![]);
+            let mut domainsep = domain_separator(&params);
             domainsep.commit_statement::<_, _, 4>(&params);
             domainsep.add_whir_proof::<_, _, 4>(&params);
 
@@ -322,12 +899,12 @@ mod test {
             let committer = CommitmentWriter::new(&params);
             let dft = Radix2DFTSmallBatch::<F>::default();
 
-            let mut proof =
-                WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+            let mut proof = WhirProof::<F, EF, MyMmcs<ARITY>>::from_protocol_parameters(
+                &whir_params,
+                num_variables,
+            );
 
-            let prover_data = committer
-                .commit(&dft, &mut proof, &mut prover_challenger, &mut statement)
-                .unwrap();
+            let (root, prover_data) = committer.commit(&dft, &statement.poly).unwrap();
 
             let prover = Prover(&params);
             prover
@@ -335,13 +912,24 @@ mod test {
                     &dft,
                     &mut proof,
                     &mut prover_challenger,
-                    &statement,
-                    prover_data,
+                    &mut statement,
+                    root,
+                    &prover_data,
                 )
                 .unwrap();
 
             let checkpoint_prover: EF = prover_challenger.sample_algebra_element();
 
+            // The proof fits the bounds derived from the arity and cap height of the trees.
+            let bytes = bincode::serialize(&proof).unwrap();
+            let options = bincode::DefaultOptions::new().with_fixint_encoding();
+            let decoded = WhirProof::<F, EF, MyMmcs<ARITY>>::deserialize_bounded(
+                &params,
+                &mut bincode::Deserializer::from_slice(&bytes, options),
+            )
+            .unwrap();
+            assert_eq!(bincode::serialize(&decoded).unwrap(), bytes);
+
             // Verify
             let commitment_reader = CommitmentReader::new(&params);
             let verifier = Verifier::new(&params);
@@ -350,8 +938,9 @@ mod test {
             let mut verifier_challenger = KeccakChallenger::new(inner);
             domainsep.observe_domain_separator(&mut verifier_challenger);
 
-            let parsed_commitment =
-                commitment_reader.parse_commitment::<u64, 4>(&proof, &mut verifier_challenger);
+            let parsed_commitment = commitment_reader
+                .parse_commitment::<u64, 4>(&proof, &mut verifier_challenger)
+                .unwrap();
 
             verifier
                 .verify(
@@ -368,7 +957,7 @@ mod test {
 
         #[test]
         fn test_whir_keccak_end_to_end() {
-            make_whir_things_keccak(
+            make_whir_things_keccak::<2>(
                 10,
                 FoldingFactor::Constant(4),
                 2,
@@ -376,7 +965,167 @@ mod test {
                 0,
                 1,
                 SumcheckStrategy::default(),
+                0,
+            );
+        }
+
+        #[test]
+        fn test_whir_keccak_initial_poseidon2_rounds() {
+            type Perm = Poseidon2KoalaBear<16>;
+            type PoseidonMmcs = MerkleTreeMmcs<
+                F,
+                F,
+                PaddingFreeSponge<Perm, 16, 8, 8>,
+                TruncatedPermutation<Perm, 2, 8, 16>,
+                2,
+                8,
+            >;
+            type MixedMmcs = SplitMmcs<MyMmcs<2>, PoseidonMmcs>;
+
+            let num_variables = 10;
+
+            // Keccak for the initial commitment, Poseidon2 for the rounds.
+            let u64_hash = U64Hash::new(KeccakF {});
+            let keccak_mmcs = MyMmcs::<2>::new(
+                KeccakFieldHash::new(u64_hash),
+                KeccakCompress::new(u64_hash),
+                0,
+            );
+            let mut rng = SmallRng::seed_from_u64(1);
+            let perm = Perm::new_from_rng_128(&mut rng);
+            let poseidon_mmcs = PoseidonMmcs::new(
+                PaddingFreeSponge::new(perm.clone()),
+                TruncatedPermutation::new(perm),
+                0,
+            );
+
+            let whir_params = ProtocolParameters {
+                security_level: 32,
+                pow_bits: 0,
+                rs_domain_initial_reduction_factor: 1,
+                folding_factor: FoldingFactor::Constant(2),
+                mmcs: SplitMmcs::new(keccak_mmcs, poseidon_mmcs),
+                soundness_type: SecurityAssumption::CapacityBound,
+                starting_log_inv_rate: 1,
+            };
+            let params = WhirConfig::<EF, F, MixedMmcs, KeccakChallenger>::new(
+                num_variables,
+                whir_params.clone(),
             );
+            assert!(params.n_rounds() > 0);
+
+            let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+            let mut statement = params.initial_statement(polynomial, SumcheckStrategy::default());
+            let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+            let verifier_statement = statement.normalize();
+
+            let mut domainsep = domain_separator(&params);
+            domainsep.commit_statement::<_, _, 4>(&params);
+            domainsep.add_whir_proof::<_, _, 4>(&params);
+
+            let new_challenger = || {
+                let inner = HashChallenger::<u8, Keccak256Hash, 32>::new(vec![], Keccak256Hash {});
+                let mut challenger = KeccakChallenger::new(inner);
+                domainsep.observe_domain_separator(&mut challenger);
+                challenger
+            };
+
+            let dft = Radix2DFTSmallBatch::<F>::default();
+            let (root, prover_data) = CommitmentWriter::new(&params)
+                .commit(&dft, &statement.poly)
+                .unwrap();
+            let mut proof = WhirProof::<F, EF, MixedMmcs>::from_protocol_parameters(
+                &whir_params,
+                num_variables,
+            );
+            let mut prover_challenger = new_challenger();
+            Prover(&params)
+                .prove(
+                    &dft,
+                    &mut proof,
+                    &mut prover_challenger,
+                    &mut statement,
+                    root,
+                    &prover_data,
+                )
+                .unwrap();
+
+            let mut verifier_challenger = new_challenger();
+            let parsed_commitment = CommitmentReader::new(&params)
+                .parse_commitment::<u64, 4>(&proof, &mut verifier_challenger)
+                .unwrap();
+            Verifier::new(&params)
+                .verify(
+                    &proof,
+                    &mut verifier_challenger,
+                    &parsed_commitment,
+                    verifier_statement,
+                )
+                .unwrap();
+
+            let checkpoint_prover: EF = prover_challenger.sample_algebra_element();
+            let checkpoint_verifier: EF = verifier_challenger.sample_algebra_element();
+            assert_eq!(checkpoint_prover, checkpoint_verifier);
+        }
+
+        #[test]
+        fn test_whir_keccak_higher_arity_end_to_end() {
+            // Openings are verified against the same `Dimensions` whatever the arity: only the
+            // number of path levels and the number of siblings per level change.
+            for (num_variables, folding_factor) in [(8, 2), (10, 4), (11, 3)] {
+                for soundness_type in [
+                    SecurityAssumption::JohnsonBound,
+                    SecurityAssumption::CapacityBound,
+                ] {
+                    make_whir_things_keccak::<4>(
+                        num_variables,
+                        FoldingFactor::Constant(folding_factor),
+                        2,
+                        soundness_type,
+                        0,
+                        1,
+                        SumcheckStrategy::default(),
+                        0,
+                    );
+                    make_whir_things_keccak::<8>(
+                        num_variables,
+                        FoldingFactor::Constant(folding_factor),
+                        2,
+                        soundness_type,
+                        0,
+                        1,
+                        SumcheckStrategy::default(),
+                        0,
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_whir_keccak_higher_arity_merkle_cap() {
+            // Higher-arity paths carry several siblings per level, minus the levels of the cap.
+            for cap_height in 0..=1 {
+                make_whir_things_keccak::<4>(
+                    10,
+                    FoldingFactor::Constant(4),
+                    2,
+                    SecurityAssumption::CapacityBound,
+                    0,
+                    1,
+                    SumcheckStrategy::default(),
+                    cap_height,
+                );
+                make_whir_things_keccak::<8>(
+                    10,
+                    FoldingFactor::Constant(4),
+                    2,
+                    SecurityAssumption::CapacityBound,
+                    0,
+                    1,
+                    SumcheckStrategy::default(),
+                    cap_height,
+                );
+            }
         }
     }
 }
@@ -0,0 +1,468 @@
+//! Search of the protocol parameters minimizing an analytical cost.
+//!
+//! The folding schedule, the starting rate, the initial domain reduction and the PoW budget all
+//! trade proof size against prover and verifier work. [`WhirOptimize::optimize`] derives the
+//! configuration of every candidate in a bounded grid, discards those which cannot reach the
+//! security level within their PoW budget, and keeps the cheapest one for an [`Objective`].
+
+use alloc::string::String;
+use core::{iter, str::FromStr};
+
+use p3_field::{BasedVectorSpace, ExtensionField, TwoAdicField};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{
+    parameters::{
+        DEFAULT_MAX_POW, FoldingFactor, ProtocolParameters, SecurityAssumption, WhirConfig,
+    },
+    whir::{
+        config::WhirConfigTryNew,
+        mmcs::{MerkleShape, RoundCommitment, RoundProof, SerializedSizes, WhirMmcs},
+    },
+};
+
+/// Largest folding factor tried.
+pub const MAX_FOLDING_FACTOR: usize = 8;
+
+/// Largest starting log inverse rate tried.
+pub const MAX_LOG_INV_RATE: usize = 4;
+
+/// Step between two PoW budgets tried, from 0 to `DEFAULT_MAX_POW`.
+pub const POW_BITS_STEP: usize = 4;
+
+/// Quantity minimized by [`WhirOptimize::optimize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// Size of the proof, in bytes.
+    ProofSize,
+    /// Work of the prover: encoding, Merkle hashing and grinding.
+    ProverTime,
+    /// Hashes computed by the verifier to check the Merkle openings.
+    VerifierHashes,
+}
+
+/// Error returned when parsing an unknown [`Objective`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown objective {0:?}, expected ProofSize, ProverTime or VerifierHashes")]
+pub struct ParseObjectiveError(pub String);
+
+impl FromStr for Objective {
+    type Err = ParseObjectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ProofSize" => Ok(Self::ProofSize),
+            "ProverTime" => Ok(Self::ProverTime),
+            "VerifierHashes" => Ok(Self::VerifierHashes),
+            _ => Err(ParseObjectiveError(s.into())),
+        }
+    }
+}
+
+/// Analytical cost of a configuration.
+///
+/// The model counts the dominant terms only: it is meant to rank configurations, not to
+/// predict timings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cost {
+    /// Commitments, OOD answers, sumcheck polynomials, final polynomial and query openings,
+    /// in bytes.
+    pub proof_bytes: usize,
+    /// Base field operations of the DFTs (`N log N` per committed codeword of size `N`,
+    /// weighted by the extension degree), one hash per committed leaf, and the expected
+    /// number of PoW attempts.
+    pub prover_ops: usize,
+    /// One leaf hash and one compression per path level below the Merkle cap, for every
+    /// query.
+    pub verifier_hashes: usize,
+}
+
+impl Cost {
+    /// Returns the component of the cost minimized for `objective`.
+    #[must_use]
+    pub const fn get(&self, objective: Objective) -> usize {
+        match objective {
+            Objective::ProofSize => self.proof_bytes,
+            Objective::ProverTime => self.prover_ops,
+            Objective::VerifierHashes => self.verifier_hashes,
+        }
+    }
+}
+
+/// Parameter search on [`WhirConfig`].
+pub trait WhirOptimize<MT>: Sized {
+    /// Analytical cost of the configuration.
+    fn estimate_cost(&self) -> Cost;
+
+    /// Returns the protocol parameters of least cost for `objective`.
+    ///
+    /// The candidates fold up to [`MAX_FOLDING_FACTOR`] variables in the first round and a
+    /// possibly different number, also up to [`MAX_FOLDING_FACTOR`], in every later round.
+    /// They start at a log inverse rate up to [`MAX_LOG_INV_RATE`], reduce the initial domain
+    /// by any factor up to the first folding factor, and allow a PoW budget from
+    /// 0 to `DEFAULT_MAX_POW` bits, by steps of [`POW_BITS_STEP`]. Candidates rejected by
+    /// [`WhirConfigTryNew::try_new`], e.g. with a domain larger than the two-adicity of the
+    /// field or an extension field too small for the security level, and candidates needing
+    /// more PoW than their budget, are skipped.
+    /// Ties are broken in favor of the candidate found first, i.e. the smallest parameters.
+    ///
+    /// Returns `None` if no candidate is feasible.
+    fn optimize(
+        num_variables: usize,
+        security_level: usize,
+        soundness_type: SecurityAssumption,
+        objective: Objective,
+        mmcs: MT,
+    ) -> Option<ProtocolParameters<MT>>;
+}
+
+impl<EF, F, MT, Challenger> WhirOptimize<MT> for WhirConfig<EF, F, MT, Challenger>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    MT: WhirMmcs<F> + Clone,
+    MT::Commitment: Serialize,
+    MT::Proof: Serialize,
+    RoundCommitment<F, MT>: Serialize,
+    RoundProof<F, MT>: Serialize,
+{
+    fn estimate_cost(&self) -> Cost {
+        let initial_sizes = SerializedSizes::of::<F, _>(&self.mmcs);
+        let round_sizes = SerializedSizes::of::<F, _>(self.mmcs.round_mmcs());
+        estimate_cost_with(self, initial_sizes, round_sizes)
+    }
+
+    fn optimize(
+        num_variables: usize,
+        security_level: usize,
+        soundness_type: SecurityAssumption,
+        objective: Objective,
+        mmcs: MT,
+    ) -> Option<ProtocolParameters<MT>> {
+        // Every candidate commits with the same MMCS: measure it once.
+        let initial_sizes = SerializedSizes::of::<F, _>(&mmcs);
+        let round_sizes = SerializedSizes::of::<F, _>(mmcs.round_mmcs());
+        let mut best: Option<(usize, ProtocolParameters<MT>)> = None;
+
+        let max_folding_factor = MAX_FOLDING_FACTOR.min(num_variables);
+        let schedules = (1..=max_folding_factor).flat_map(|first| {
+            (1..=max_folding_factor).map(move |next| {
+                if next == first {
+                    FoldingFactor::Constant(first)
+                } else {
+                    FoldingFactor::ConstantFromSecondRound(first, next)
+                }
+            })
+        });
+
+        for folding_factor in schedules {
+            for starting_log_inv_rate in 1..=MAX_LOG_INV_RATE {
+                for rs_domain_initial_reduction_factor in 1..=folding_factor.at_round(0) {
+                    for pow_bits in (0..=DEFAULT_MAX_POW).step_by(POW_BITS_STEP) {
+                        let params = ProtocolParameters {
+                            security_level,
+                            pow_bits,
+                            folding_factor,
+                            mmcs: mmcs.clone(),
+                            soundness_type,
+                            starting_log_inv_rate,
+                            rs_domain_initial_reduction_factor,
+                        };
+                        let Ok(config) = Self::try_new(num_variables, params.clone()) else {
+                            continue;
+                        };
+                        if !config.check_pow_bits() {
+                            continue;
+                        }
+
+                        let cost =
+                            estimate_cost_with(&config, initial_sizes, round_sizes).get(objective);
+                        if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                            best = Some((cost, params));
+                        }
+                    }
+                }
+            }
+        }
+
+        best.map(|(_, params)| params)
+    }
+}
+
+/// Cost of `config`, given the serialized sizes of its MMCS and of its round MMCS.
+fn estimate_cost_with<EF, F, MT, Challenger>(
+    config: &WhirConfig<EF, F, MT, Challenger>,
+    initial_sizes: SerializedSizes,
+    round_sizes: SerializedSizes,
+) -> Cost
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    MT: WhirMmcs<F>,
+{
+    let extension_degree = <EF as BasedVectorSpace<F>>::DIMENSION;
+
+    let mut proof_bytes = initial_sizes.commitment
+        + config.n_rounds() * round_sizes.commitment
+        + (config.commitment_ood_samples
+            + config
+                .round_parameters
+                .iter()
+                .map(|round| round.ood_samples)
+                .sum::<usize>())
+            * size_of::<EF>()
+        // Each folded variable sends a quadratic polynomial, as three evaluations.
+        + 3 * config.num_variables * size_of::<EF>()
+        + (1 << config.final_sumcheck_rounds) * size_of::<EF>();
+    let mut prover_ops = 0;
+    let mut verifier_hashes = 0;
+
+    // Every commitment is opened by the queries of the round following it.
+    let final_round = config.final_round_config();
+    let openings = config
+        .round_parameters
+        .iter()
+        .chain(iter::once(&final_round));
+    for (index, round) in openings.enumerate() {
+        let (shape, sizes, leaf_bytes, width): (&dyn MerkleShape, _, _, _) = if index == 0 {
+            (&config.mmcs, initial_sizes, size_of::<F>(), 1)
+        } else {
+            (
+                config.mmcs.round_mmcs(),
+                round_sizes,
+                size_of::<EF>(),
+                extension_degree,
+            )
+        };
+        let num_leaves = round.domain_size >> round.folding_factor;
+        let path_levels = shape
+            .tree_height(num_leaves)
+            .saturating_sub(shape.cap_height());
+
+        proof_bytes += round.num_queries
+            * ((leaf_bytes << round.folding_factor)
+                + shape.max_path_digests(num_leaves) * sizes.digest);
+        prover_ops +=
+            round.domain_size * round.domain_size.ilog2() as usize * width + round.domain_size;
+        // One leaf hash, then one compression per level below the cap.
+        verifier_hashes += round.num_queries * (path_levels + 1);
+    }
+
+    // Expected PoW attempts: before the queries of every round, and per folded variable.
+    prover_ops += config
+        .round_parameters
+        .iter()
+        .map(|round| 1 << round.pow_bits)
+        .sum::<usize>()
+        + (1 << config.final_pow_bits)
+        + (1 << config.starting_folding_pow_bits) * config.folding_factor.at_round(0)
+        + config
+            .round_parameters
+            .iter()
+            .enumerate()
+            .map(|(index, round)| {
+                (1 << round.folding_pow_bits) * config.folding_factor.at_round(index + 1)
+            })
+            .sum::<usize>()
+        + (1 << config.final_folding_pow_bits) * config.final_sumcheck_rounds;
+
+    Cost {
+        proof_bytes,
+        prover_ops,
+        verifier_hashes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+    type MyMmcs = MerkleTreeMmcs<F, F, MyHash, MyCompress, 2, 8>;
+    type MyConfig = WhirConfig<EF, F, MyMmcs, MyChallenger>;
+
+    const OBJECTIVES: [Objective; 3] = [
+        Objective::ProofSize,
+        Objective::ProverTime,
+        Objective::VerifierHashes,
+    ];
+
+    fn mmcs() -> MyMmcs {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0)
+    }
+
+    #[test]
+    fn test_optimize_beats_grid() {
+        let (num_variables, security_level) = (14, 80);
+        let soundness_type = SecurityAssumption::CapacityBound;
+
+        for objective in OBJECTIVES {
+            let params = MyConfig::optimize(
+                num_variables,
+                security_level,
+                soundness_type,
+                objective,
+                mmcs(),
+            )
+            .unwrap();
+            let config = MyConfig::try_new(num_variables, params).unwrap();
+            assert!(config.check_pow_bits());
+            let best = config.estimate_cost().get(objective);
+
+            // Hand-picked candidates, all inside the search space.
+            let schedules = [
+                FoldingFactor::Constant(2),
+                FoldingFactor::Constant(3),
+                FoldingFactor::Constant(4),
+                FoldingFactor::ConstantFromSecondRound(5, 3),
+                FoldingFactor::ConstantFromSecondRound(3, 4),
+            ];
+            for folding_factor in schedules {
+                for starting_log_inv_rate in 1..=2 {
+                    let candidate = MyConfig::new(
+                        num_variables,
+                        ProtocolParameters {
+                            security_level,
+                            pow_bits: DEFAULT_MAX_POW - DEFAULT_MAX_POW % POW_BITS_STEP,
+                            folding_factor,
+                            mmcs: mmcs(),
+                            soundness_type,
+                            starting_log_inv_rate,
+                            rs_domain_initial_reduction_factor: 1,
+                        },
+                    );
+                    if candidate.check_pow_bits() {
+                        assert!(best <= candidate.estimate_cost().get(objective));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimize_objectives_disagree() {
+        let (num_variables, security_level) = (16, 100);
+        let soundness_type = SecurityAssumption::CapacityBound;
+        let optima = OBJECTIVES.map(|objective| {
+            let params = MyConfig::optimize(
+                num_variables,
+                security_level,
+                soundness_type,
+                objective,
+                mmcs(),
+            )
+            .unwrap();
+            MyConfig::new(num_variables, params).estimate_cost()
+        });
+
+        // Each optimum is the best on its own objective.
+        for (index, objective) in OBJECTIVES.into_iter().enumerate() {
+            for other in &optima {
+                assert!(optima[index].get(objective) <= other.get(objective));
+            }
+        }
+        // Proof size and prover time pull the parameters apart.
+        assert_ne!(optima[0], optima[1]);
+    }
+
+    #[test]
+    fn test_optimize_respects_extension_field() {
+        // The quartic extension of BabyBear has 123 bits: under the Johnson bound, 100 bits
+        // of security leave room for an initial domain of size 2^11 at most.
+        let soundness_type = SecurityAssumption::JohnsonBound;
+        for objective in OBJECTIVES {
+            let params = MyConfig::optimize(10, 100, soundness_type, objective, mmcs()).unwrap();
+            assert_eq!(params.starting_log_inv_rate, 1);
+            assert!(MyConfig::try_new(10, params).is_ok());
+
+            assert!(MyConfig::optimize(11, 100, soundness_type, objective, mmcs()).is_none());
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost_monotone_in_queries() {
+        // A higher security level needs more queries: every component grows.
+        let cost = |security_level| {
+            MyConfig::new(
+                12,
+                ProtocolParameters {
+                    security_level,
+                    pow_bits: 0,
+                    folding_factor: FoldingFactor::Constant(2),
+                    mmcs: mmcs(),
+                    soundness_type: SecurityAssumption::CapacityBound,
+                    starting_log_inv_rate: 1,
+                    rs_domain_initial_reduction_factor: 1,
+                },
+            )
+            .estimate_cost()
+        };
+        let (low, high) = (cost(32), cost(100));
+
+        assert!(low.proof_bytes < high.proof_bytes);
+        assert!(low.verifier_hashes < high.verifier_hashes);
+        assert!(low.prover_ops <= high.prover_ops);
+    }
+
+    #[test]
+    fn test_estimate_cost_follows_merkle_shape() {
+        let config = |cap_height| {
+            let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+            MyConfig::new(
+                12,
+                ProtocolParameters {
+                    security_level: 32,
+                    pow_bits: 0,
+                    folding_factor: FoldingFactor::Constant(2),
+                    mmcs: MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), cap_height),
+                    soundness_type: SecurityAssumption::CapacityBound,
+                    starting_log_inv_rate: 1,
+                    rs_domain_initial_reduction_factor: 1,
+                },
+            )
+        };
+        let (uncapped, capped) = (config(0), config(2));
+        let queries: usize = uncapped
+            .round_parameters
+            .iter()
+            .map(|round| round.num_queries)
+            .sum::<usize>()
+            + uncapped.final_round_config().num_queries;
+
+        // Eight BabyBear elements per digest, each taking up to five bytes as a varint.
+        let digest_bytes = SerializedSizes::of::<F, _>(&uncapped.mmcs).digest;
+        assert!((8..=40).contains(&digest_bytes));
+
+        // A cap of height 2 removes two levels of every binary path: one compression and one
+        // sibling each, while every commitment publishes three more digests.
+        let (uncapped, capped) = (uncapped.estimate_cost(), capped.estimate_cost());
+        assert_eq!(
+            uncapped.verifier_hashes - capped.verifier_hashes,
+            2 * queries
+        );
+        assert!(capped.proof_bytes < uncapped.proof_bytes);
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        for objective in OBJECTIVES {
+            assert_eq!(alloc::format!("{objective:?}").parse(), Ok(objective));
+        }
+        assert!("Fastest".parse::<Objective>().is_err());
+    }
+}
@@ -0,0 +1,293 @@
+//! Named security presets for the common base fields.
+//!
+//! `WhirConfig::new` accepts any `security_level`, even one the extension field cannot
+//! support: every challenge is sampled from the extension, so no amount of queries or PoW
+//! makes the protocol sounder than its proximity-gaps error, which grows with the size of the
+//! evaluation domain. The presets choose the soundness assumption and the PoW budget of a
+//! target level, and the smallest extension of the base field reaching it.
+//!
+//! The field-size requirement is the conservative bound
+//!
+//! ```text
+//! log2 |EF| >= security_level + loss,   loss = log2 n      (unique decoding, capacity bound)
+//!                                       loss = 2 * log2 n  (Johnson bound)
+//! ```
+//!
+//! where `n` is the size of the initial evaluation domain. The number of OOD samples then
+//! follows from the assumption and the extension size, and is derived by `WhirConfig::new`.
+
+use p3_baby_bear::BabyBear;
+use p3_field::{ExtensionField, Field};
+use p3_goldilocks::Goldilocks;
+use p3_koala_bear::KoalaBear;
+use thiserror::Error;
+
+use crate::parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption};
+
+/// Extension field too small for the requested security level.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error(
+    "{security_level}-bit security under {soundness_type:?} with a domain of size 2^{log_domain_size} \
+     needs a {required_bits}-bit extension field, got {extension_bits} bits"
+)]
+pub struct ExtensionFieldTooSmall {
+    /// Requested security level.
+    pub security_level: usize,
+    /// Soundness assumption.
+    pub soundness_type: SecurityAssumption,
+    /// Log size of the initial evaluation domain.
+    pub log_domain_size: usize,
+    /// Bits of the extension field needed.
+    pub required_bits: usize,
+    /// Bits of the extension field, rounded down.
+    pub extension_bits: usize,
+}
+
+/// Bits of an extension field needed for `security_level` bits of security.
+#[must_use]
+pub const fn required_extension_bits(
+    security_level: usize,
+    soundness_type: SecurityAssumption,
+    log_domain_size: usize,
+) -> usize {
+    let loss = match soundness_type {
+        SecurityAssumption::JohnsonBound => 2 * log_domain_size,
+        SecurityAssumption::UniqueDecoding | SecurityAssumption::CapacityBound => log_domain_size,
+    };
+    security_level + loss
+}
+
+/// Checks that an extension field of `extension_bits` bits supports `security_level`.
+///
+/// # Errors
+/// Returns an error if the extension field is too small.
+pub const fn check_extension_bits(
+    extension_bits: usize,
+    security_level: usize,
+    soundness_type: SecurityAssumption,
+    log_domain_size: usize,
+) -> Result<(), ExtensionFieldTooSmall> {
+    let required_bits = required_extension_bits(security_level, soundness_type, log_domain_size);
+    if extension_bits >= required_bits {
+        Ok(())
+    } else {
+        Err(ExtensionFieldTooSmall {
+            security_level,
+            soundness_type,
+            log_domain_size,
+            required_bits,
+            extension_bits,
+        })
+    }
+}
+
+/// Checks that `EF` supports `security_level`, for an initial domain of size
+/// `2^log_domain_size`.
+///
+/// # Errors
+/// Returns an error if the extension field is too small.
+pub fn check_extension_field<F: Field, EF: ExtensionField<F>>(
+    security_level: usize,
+    soundness_type: SecurityAssumption,
+    log_domain_size: usize,
+) -> Result<(), ExtensionFieldTooSmall> {
+    check_extension_bits(
+        EF::order().bits() as usize - 1,
+        security_level,
+        soundness_type,
+        log_domain_size,
+    )
+}
+
+/// Base field with security presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetField {
+    /// `2^31 - 2^27 + 1`, with extensions of degree 4, 5 and 8.
+    BabyBear,
+    /// `2^31 - 2^24 + 1`, with extensions of degree 4 and 8.
+    KoalaBear,
+    /// `2^64 - 2^32 + 1`, with extensions of degree 2 and 5.
+    Goldilocks,
+}
+
+impl PresetField {
+    /// Degrees of the extensions implemented for the field, in increasing order.
+    #[must_use]
+    pub const fn extension_degrees(self) -> &'static [usize] {
+        match self {
+            Self::BabyBear => &[4, 5, 8],
+            Self::KoalaBear => &[4, 8],
+            Self::Goldilocks => &[2, 5],
+        }
+    }
+
+    /// Bits of the extension of degree `degree`, rounded down.
+    #[must_use]
+    pub fn extension_bits(self, degree: usize) -> usize {
+        let order = match self {
+            Self::BabyBear => BabyBear::order(),
+            Self::KoalaBear => KoalaBear::order(),
+            Self::Goldilocks => Goldilocks::order(),
+        };
+        order.pow(degree as u32).bits() as usize - 1
+    }
+}
+
+/// Named security target: a level, a soundness assumption and a PoW budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityPreset {
+    /// 100 bits, proven under the Johnson bound.
+    Proven100,
+    /// 128 bits, proven under the Johnson bound.
+    Proven128,
+    /// 100 bits, conjectured up to the list-decoding capacity.
+    Conjectured100,
+    /// 128 bits, conjectured up to the list-decoding capacity.
+    Conjectured128,
+}
+
+impl SecurityPreset {
+    /// Bits of security.
+    #[must_use]
+    pub const fn security_level(self) -> usize {
+        match self {
+            Self::Proven100 | Self::Conjectured100 => 100,
+            Self::Proven128 | Self::Conjectured128 => 128,
+        }
+    }
+
+    /// Soundness assumption the level holds under.
+    #[must_use]
+    pub const fn soundness_type(self) -> SecurityAssumption {
+        match self {
+            Self::Proven100 | Self::Proven128 => SecurityAssumption::JohnsonBound,
+            Self::Conjectured100 | Self::Conjectured128 => SecurityAssumption::CapacityBound,
+        }
+    }
+
+    /// Maximum PoW per round, in bits, traded against queries.
+    #[must_use]
+    pub const fn pow_bits(self) -> usize {
+        match self {
+            Self::Proven100 | Self::Conjectured100 => 16,
+            Self::Proven128 | Self::Conjectured128 => 20,
+        }
+    }
+
+    /// Smallest extension degree of `field` reaching the preset, for an initial domain of
+    /// size `2^log_domain_size`.
+    ///
+    /// # Errors
+    /// Returns the error of the largest extension if none is large enough.
+    pub fn extension_degree(
+        self,
+        field: PresetField,
+        log_domain_size: usize,
+    ) -> Result<usize, ExtensionFieldTooSmall> {
+        let check = |degree| {
+            check_extension_bits(
+                field.extension_bits(degree),
+                self.security_level(),
+                self.soundness_type(),
+                log_domain_size,
+            )
+        };
+        let degrees = field.extension_degrees();
+        degrees
+            .iter()
+            .copied()
+            .find(|&degree| check(degree).is_ok())
+            .ok_or_else(|| check(degrees[degrees.len() - 1]).unwrap_err())
+    }
+
+    /// Protocol parameters of the preset.
+    ///
+    /// The extension field is chosen by the caller, through the types of the configuration:
+    /// check it with [`check_extension_field`] or [`Self::extension_degree`].
+    pub const fn protocol_parameters<MT>(
+        self,
+        folding_factor: FoldingFactor,
+        starting_log_inv_rate: usize,
+        mmcs: MT,
+    ) -> ProtocolParameters<MT> {
+        ProtocolParameters {
+            security_level: self.security_level(),
+            pow_bits: self.pow_bits(),
+            folding_factor,
+            mmcs,
+            soundness_type: self.soundness_type(),
+            starting_log_inv_rate,
+            rs_domain_initial_reduction_factor: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::extension::BinomialExtensionField;
+
+    use super::*;
+
+    const ASSUMPTIONS: [SecurityAssumption; 3] = [
+        SecurityAssumption::UniqueDecoding,
+        SecurityAssumption::JohnsonBound,
+        SecurityAssumption::CapacityBound,
+    ];
+
+    #[test]
+    fn test_koala_bear_quartic_below_128_bits() {
+        type EF = BinomialExtensionField<KoalaBear, 4>;
+
+        // 123 bits: no assumption reaches 128 bits, even on the smallest domain.
+        for soundness_type in ASSUMPTIONS {
+            let err = check_extension_field::<KoalaBear, EF>(128, soundness_type, 1).unwrap_err();
+            assert_eq!(err.extension_bits, 123);
+        }
+        assert!(
+            check_extension_field::<KoalaBear, EF>(100, SecurityAssumption::CapacityBound, 20)
+                .is_ok()
+        );
+        assert!(
+            check_extension_field::<KoalaBear, EF>(100, SecurityAssumption::JohnsonBound, 20)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_extension_bits_match_fields() {
+        assert_eq!(
+            PresetField::BabyBear.extension_bits(4),
+            BinomialExtensionField::<BabyBear, 4>::order().bits() as usize - 1
+        );
+        assert_eq!(
+            PresetField::Goldilocks.extension_bits(2),
+            BinomialExtensionField::<Goldilocks, 2>::order().bits() as usize - 1
+        );
+    }
+
+    #[test]
+    fn test_preset_extension_degrees() {
+        use PresetField::{BabyBear, Goldilocks, KoalaBear};
+        use SecurityPreset::{Conjectured100, Conjectured128, Proven100, Proven128};
+
+        for (preset, field, log_domain_size, degree) in [
+            (Conjectured100, KoalaBear, 20, 4),
+            (Conjectured100, KoalaBear, 26, 8),
+            (Conjectured128, KoalaBear, 20, 8),
+            (Proven100, BabyBear, 20, 5),
+            (Proven128, BabyBear, 20, 8),
+            (Conjectured100, Goldilocks, 24, 2),
+            (Proven128, Goldilocks, 24, 5),
+        ] {
+            assert_eq!(
+                preset.extension_degree(field, log_domain_size),
+                Ok(degree),
+                "{preset:?} over {field:?}"
+            );
+        }
+
+        // The largest extension bounds the domain.
+        let err = Proven128.extension_degree(KoalaBear, 60).unwrap_err();
+        assert_eq!(err.extension_bits, KoalaBear.extension_bits(8));
+    }
+}
@@ -0,0 +1,1097 @@
+//! Deserialization of proofs with every collection length bounded by the configuration.
+//!
+//! The derived `Deserialize` of [`WhirProof`] trusts the length prefixes of the input, so a
+//! hostile proof can announce billions of rounds, queries or evaluations and make the
+//! verifier allocate before any check runs. [`WhirProof::deserialize_bounded`] instead
+//! rejects any collection longer than what the configuration permits as soon as its length
+//! is known.
+
+use alloc::vec::Vec;
+use core::{fmt, marker::PhantomData};
+
+use p3_field::{ExtensionField, TwoAdicField};
+use serde::{
+    Deserialize,
+    de::{
+        self, DeserializeSeed, Deserializer, EnumAccess, Error as _, MapAccess, SeqAccess,
+        VariantAccess, Visitor,
+    },
+};
+
+use super::{QueryOpening, WhirProof, WhirRoundProof};
+use crate::{
+    parameters::{RoundConfig, WhirConfig},
+    whir::mmcs::{MerkleShape, RoundCommitment, RoundProof, WhirMmcs},
+};
+
+/// Default maximum number of evaluation claims embedded in a proof.
+pub const DEFAULT_MAX_CLAIMS: usize = 1 << 10;
+
+/// Bounds on the query openings of one round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryBounds {
+    /// Maximum number of openings.
+    pub queries: usize,
+    /// Maximum number of values in an opened leaf.
+    pub leaf_width: usize,
+    /// Maximum number of digests in a Merkle authentication path.
+    pub merkle_path: usize,
+}
+
+impl QueryBounds {
+    /// Bounds on the openings of `round`, against a tree committed with `mmcs`.
+    fn new<F: TwoAdicField>(round: &RoundConfig<F>, mmcs: &impl MerkleShape) -> Self {
+        Self {
+            queries: round.num_queries,
+            leaf_width: 1 << round.folding_factor,
+            merkle_path: mmcs.max_path_digests(round.domain_size >> round.folding_factor),
+        }
+    }
+}
+
+/// Bounds on the data of one WHIR round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundBounds {
+    /// Maximum number of OOD answers.
+    pub ood_answers: usize,
+    /// Bounds on the STIR query openings.
+    pub queries: QueryBounds,
+}
+
+/// Maximum length of every variable-length collection of a proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofBounds {
+    /// Maximum number of embedded evaluation claims.
+    pub claims: usize,
+    /// Number of variables of the committed polynomial, bounding each claimed point.
+    pub num_variables: usize,
+    /// Maximum length of any collection inside a commitment.
+    pub commitment: usize,
+    /// Maximum number of initial OOD answers.
+    pub initial_ood_answers: usize,
+    /// Maximum length of any collection inside sumcheck data.
+    pub sumcheck: usize,
+    /// Bounds of each round; the proof may not carry more rounds.
+    pub rounds: Vec<RoundBounds>,
+    /// Maximum number of evaluations of the final polynomial.
+    pub final_poly: usize,
+    /// Bounds on the final query openings.
+    pub final_queries: QueryBounds,
+}
+
+impl ProofBounds {
+    /// Derives the bounds of the proofs produced with `config`.
+    ///
+    /// Embedded claims are limited to [`DEFAULT_MAX_CLAIMS`], which the configuration does
+    /// not constrain; raise [`Self::claims`] to accept more.
+    pub fn new<EF, F, MT, Challenger>(config: &WhirConfig<EF, F, MT, Challenger>) -> Self
+    where
+        F: TwoAdicField,
+        EF: ExtensionField<F> + TwoAdicField,
+        MT: WhirMmcs<F>,
+    {
+        // The queries of the first round open the initial commitment, the later ones open
+        // a round commitment.
+        let query_bounds = |round_index: usize, round: &RoundConfig<F>| {
+            if round_index == 0 {
+                QueryBounds::new(round, &config.mmcs)
+            } else {
+                QueryBounds::new(round, config.mmcs.round_mmcs())
+            }
+        };
+        let final_round_config = config.final_round_config();
+        let largest_domain = config
+            .round_parameters
+            .iter()
+            .chain([&final_round_config])
+            .map(|round| round.domain_size >> round.folding_factor)
+            .max()
+            .unwrap_or(1);
+        let sumcheck_rounds = (0..=config.n_rounds())
+            .map(|round| config.folding_factor.at_round(round))
+            .chain([config.final_sumcheck_rounds])
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            claims: DEFAULT_MAX_CLAIMS,
+            num_variables: config.num_variables,
+            commitment: largest_domain,
+            initial_ood_answers: config.commitment_ood_samples,
+            // Round polynomials are quadratic, sent as at most three evaluations.
+            sumcheck: sumcheck_rounds.max(3),
+            rounds: config
+                .round_parameters
+                .iter()
+                .enumerate()
+                .map(|(round_index, round)| RoundBounds {
+                    ood_answers: round.ood_samples,
+                    queries: query_bounds(round_index, round),
+                })
+                .collect(),
+            final_poly: 1 << final_round_config.num_variables,
+            final_queries: query_bounds(config.n_rounds(), &final_round_config),
+        }
+    }
+}
+
+impl<F, EF, MT> WhirProof<F, EF, MT>
+where
+    F: Send + Sync + Clone,
+    MT: WhirMmcs<F>,
+{
+    /// Deserializes a proof, rejecting any collection longer than `config` permits.
+    ///
+    /// Lengths are checked as soon as the deserializer announces them, before anything is
+    /// allocated. The format must encode structs as sequences, as bincode does; for the
+    /// encoding of `bincode::serialize`, pass
+    /// `bincode::Deserializer::with_reader(reader, DefaultOptions::new().with_fixint_encoding())`.
+    ///
+    /// # Errors
+    /// Returns a deserialization error if the input is malformed or exceeds the bounds.
+    pub fn deserialize_bounded<'de, D, Challenger>(
+        config: &WhirConfig<EF, F, MT, Challenger>,
+        deserializer: D,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        F: TwoAdicField + Deserialize<'de>,
+        EF: ExtensionField<F> + TwoAdicField + Deserialize<'de>,
+        MT::Commitment: Deserialize<'de>,
+        MT::Proof: Deserialize<'de>,
+        RoundCommitment<F, MT>: Deserialize<'de>,
+        RoundProof<F, MT>: Deserialize<'de>,
+    {
+        Self::deserialize_with_bounds(&ProofBounds::new(config), deserializer)
+    }
+
+    /// Deserializes a proof, rejecting any collection longer than `bounds` permit.
+    ///
+    /// # Errors
+    /// Same as [`Self::deserialize_bounded`].
+    pub fn deserialize_with_bounds<'de, D>(
+        bounds: &ProofBounds,
+        deserializer: D,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+        F: Deserialize<'de>,
+        EF: Deserialize<'de>,
+        MT::Commitment: Deserialize<'de>,
+        MT::Proof: Deserialize<'de>,
+        RoundCommitment<F, MT>: Deserialize<'de>,
+        RoundProof<F, MT>: Deserialize<'de>,
+    {
+        deserializer.deserialize_struct(
+            "WhirProof",
+            &[
+                "claims",
+                "config_fingerprint",
+                "initial_commitment",
+                "initial_ood_answers",
+                "initial_sumcheck",
+                "rounds",
+                "final_poly",
+                "final_pow_witness",
+                "final_queries",
+                "final_sumcheck",
+            ],
+            ProofVisitor {
+                bounds,
+                _marker: PhantomData,
+            },
+        )
+    }
+}
+
+/// Returns an error if a collection announces more than `max_len` elements.
+fn check_len<E: de::Error>(len: Option<usize>, max_len: usize) -> Result<(), E> {
+    match len {
+        Some(len) if len > max_len => Err(E::custom(format_args!(
+            "collection of {len} elements exceeds the bound of {max_len}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the field at `index` of a struct, bounding its collections by `max_len`.
+fn next_field<'de, A, T>(
+    seq: &mut A,
+    index: usize,
+    max_len: usize,
+    expected: &dyn de::Expected,
+) -> Result<T, A::Error>
+where
+    A: SeqAccess<'de>,
+    T: Deserialize<'de>,
+{
+    seq.next_element_seed(Capped::new(max_len))?
+        .ok_or_else(|| A::Error::invalid_length(index, expected))
+}
+
+struct ProofVisitor<'a, F, EF, MT> {
+    bounds: &'a ProofBounds,
+    _marker: PhantomData<(F, EF, MT)>,
+}
+
+impl<'de, F, EF, MT> Visitor<'de> for ProofVisitor<'_, F, EF, MT>
+where
+    F: Send + Sync + Clone + Deserialize<'de>,
+    EF: Deserialize<'de>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Deserialize<'de>,
+    MT::Proof: Deserialize<'de>,
+    RoundCommitment<F, MT>: Deserialize<'de>,
+    RoundProof<F, MT>: Deserialize<'de>,
+{
+    type Value = WhirProof<F, EF, MT>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("struct WhirProof")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let bounds = self.bounds;
+        let claims_len = bounds.claims.max(bounds.num_variables);
+        Ok(WhirProof {
+            claims: next_field(&mut seq, 0, claims_len, &self)?,
+            config_fingerprint: next_field(&mut seq, 1, 0, &self)?,
+            initial_commitment: next_field(&mut seq, 2, bounds.commitment, &self)?,
+            initial_ood_answers: next_field(&mut seq, 3, bounds.initial_ood_answers, &self)?,
+            initial_sumcheck: next_field(&mut seq, 4, bounds.sumcheck, &self)?,
+            rounds: seq
+                .next_element_seed(RoundsSeed {
+                    bounds,
+                    _marker: PhantomData,
+                })?
+                .ok_or_else(|| A::Error::invalid_length(5, &self))?,
+            final_poly: next_field(&mut seq, 6, bounds.final_poly, &self)?,
+            final_pow_witness: next_field(&mut seq, 7, 0, &self)?,
+            final_queries: seq
+                .next_element_seed(QueriesSeed {
+                    bounds: bounds.final_queries,
+                    _marker: PhantomData,
+                })?
+                .ok_or_else(|| A::Error::invalid_length(8, &self))?,
+            final_sumcheck: next_field(&mut seq, 9, bounds.sumcheck, &self)?,
+        })
+    }
+}
+
+/// Seed of the round proofs, the `i`-th one being bounded by `bounds.rounds[i]`.
+struct RoundsSeed<'a, F, EF, MT> {
+    bounds: &'a ProofBounds,
+    _marker: PhantomData<(F, EF, MT)>,
+}
+
+impl<'de, F, EF, MT> DeserializeSeed<'de> for RoundsSeed<'_, F, EF, MT>
+where
+    F: Send + Sync + Clone + Deserialize<'de>,
+    EF: Deserialize<'de>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Deserialize<'de>,
+    MT::Proof: Deserialize<'de>,
+    RoundCommitment<F, MT>: Deserialize<'de>,
+    RoundProof<F, MT>: Deserialize<'de>,
+{
+    type Value = Vec<WhirRoundProof<F, EF, MT>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F, EF, MT> Visitor<'de> for RoundsSeed<'_, F, EF, MT>
+where
+    F: Send + Sync + Clone + Deserialize<'de>,
+    EF: Deserialize<'de>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Deserialize<'de>,
+    MT::Proof: Deserialize<'de>,
+    RoundCommitment<F, MT>: Deserialize<'de>,
+    RoundProof<F, MT>: Deserialize<'de>,
+{
+    type Value = Vec<WhirRoundProof<F, EF, MT>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence of WHIR round proofs")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let max_rounds = self.bounds.rounds.len();
+        check_len(seq.size_hint(), max_rounds)?;
+
+        let mut rounds = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        loop {
+            let Some(round_bounds) = self.bounds.rounds.get(rounds.len()) else {
+                // Every permitted round is read, the sequence must end here.
+                if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                    return Err(A::Error::custom(format_args!(
+                        "proof has more than {max_rounds} rounds"
+                    )));
+                }
+                return Ok(rounds);
+            };
+            let seed = RoundSeed {
+                bounds: self.bounds,
+                round: round_bounds,
+                _marker: PhantomData,
+            };
+            match seq.next_element_seed(seed)? {
+                Some(round) => rounds.push(round),
+                None => return Ok(rounds),
+            }
+        }
+    }
+}
+
+struct RoundSeed<'a, F, EF, MT> {
+    bounds: &'a ProofBounds,
+    round: &'a RoundBounds,
+    _marker: PhantomData<(F, EF, MT)>,
+}
+
+impl<'de, F, EF, MT> DeserializeSeed<'de> for RoundSeed<'_, F, EF, MT>
+where
+    F: Send + Sync + Clone + Deserialize<'de>,
+    EF: Deserialize<'de>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Deserialize<'de>,
+    MT::Proof: Deserialize<'de>,
+    RoundCommitment<F, MT>: Deserialize<'de>,
+    RoundProof<F, MT>: Deserialize<'de>,
+{
+    type Value = WhirRoundProof<F, EF, MT>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_struct(
+            "WhirRoundProof",
+            &[
+                "commitment",
+                "ood_answers",
+                "pow_witness",
+                "queries",
+                "sumcheck",
+            ],
+            self,
+        )
+    }
+}
+
+impl<'de, F, EF, MT> Visitor<'de> for RoundSeed<'_, F, EF, MT>
+where
+    F: Send + Sync + Clone + Deserialize<'de>,
+    EF: Deserialize<'de>,
+    MT: WhirMmcs<F>,
+    MT::Commitment: Deserialize<'de>,
+    MT::Proof: Deserialize<'de>,
+    RoundCommitment<F, MT>: Deserialize<'de>,
+    RoundProof<F, MT>: Deserialize<'de>,
+{
+    type Value = WhirRoundProof<F, EF, MT>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("struct WhirRoundProof")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        Ok(WhirRoundProof {
+            commitment: next_field(&mut seq, 0, self.bounds.commitment, &self)?,
+            ood_answers: next_field(&mut seq, 1, self.round.ood_answers, &self)?,
+            pow_witness: next_field(&mut seq, 2, 0, &self)?,
+            queries: seq
+                .next_element_seed(QueriesSeed {
+                    bounds: self.round.queries,
+                    _marker: PhantomData,
+                })?
+                .ok_or_else(|| A::Error::invalid_length(3, &self))?,
+            sumcheck: next_field(&mut seq, 4, self.bounds.sumcheck, &self)?,
+        })
+    }
+}
+
+/// Seed of the query openings of a round.
+struct QueriesSeed<F, EF, Proof, ExtProof> {
+    bounds: QueryBounds,
+    _marker: PhantomData<(F, EF, Proof, ExtProof)>,
+}
+
+impl<'de, F, EF, Proof, ExtProof> DeserializeSeed<'de> for QueriesSeed<F, EF, Proof, ExtProof>
+where
+    F: Deserialize<'de>,
+    EF: Deserialize<'de>,
+    Proof: Deserialize<'de>,
+    ExtProof: Deserialize<'de>,
+{
+    type Value = Vec<QueryOpening<F, EF, Proof, ExtProof>>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F, EF, Proof, ExtProof> Visitor<'de> for QueriesSeed<F, EF, Proof, ExtProof>
+where
+    F: Deserialize<'de>,
+    EF: Deserialize<'de>,
+    Proof: Deserialize<'de>,
+    ExtProof: Deserialize<'de>,
+{
+    type Value = Vec<QueryOpening<F, EF, Proof, ExtProof>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence of query openings")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        check_len(seq.size_hint(), self.bounds.queries)?;
+
+        let mut queries = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(query) = seq.next_element_seed(OpeningSeed {
+            bounds: self.bounds,
+            _marker: PhantomData,
+        })? {
+            queries.push(query);
+            check_len(Some(queries.len()), self.bounds.queries)?;
+        }
+        Ok(queries)
+    }
+}
+
+/// Variant of a [`QueryOpening`], matching its derived encoding.
+#[derive(Deserialize)]
+#[serde(variant_identifier)]
+enum OpeningKind {
+    #[serde(rename = "base")]
+    Base,
+    #[serde(rename = "extension")]
+    Extension,
+}
+
+struct OpeningSeed<F, EF, Proof, ExtProof> {
+    bounds: QueryBounds,
+    _marker: PhantomData<(F, EF, Proof, ExtProof)>,
+}
+
+impl<'de, F, EF, Proof, ExtProof> DeserializeSeed<'de> for OpeningSeed<F, EF, Proof, ExtProof>
+where
+    F: Deserialize<'de>,
+    EF: Deserialize<'de>,
+    Proof: Deserialize<'de>,
+    ExtProof: Deserialize<'de>,
+{
+    type Value = QueryOpening<F, EF, Proof, ExtProof>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_enum("QueryOpening", &["base", "extension"], self)
+    }
+}
+
+impl<'de, F, EF, Proof, ExtProof> Visitor<'de> for OpeningSeed<F, EF, Proof, ExtProof>
+where
+    F: Deserialize<'de>,
+    EF: Deserialize<'de>,
+    Proof: Deserialize<'de>,
+    ExtProof: Deserialize<'de>,
+{
+    type Value = QueryOpening<F, EF, Proof, ExtProof>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("enum QueryOpening")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (kind, variant) = data.variant::<OpeningKind>()?;
+        variant.struct_variant(
+            &["values", "proof"],
+            OpeningFieldsVisitor {
+                kind,
+                bounds: self.bounds,
+                _marker: PhantomData,
+            },
+        )
+    }
+}
+
+struct OpeningFieldsVisitor<F, EF, Proof, ExtProof> {
+    kind: OpeningKind,
+    bounds: QueryBounds,
+    _marker: PhantomData<(F, EF, Proof, ExtProof)>,
+}
+
+impl<'de, F, EF, Proof, ExtProof> Visitor<'de> for OpeningFieldsVisitor<F, EF, Proof, ExtProof>
+where
+    F: Deserialize<'de>,
+    EF: Deserialize<'de>,
+    Proof: Deserialize<'de>,
+    ExtProof: Deserialize<'de>,
+{
+    type Value = QueryOpening<F, EF, Proof, ExtProof>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a query opening")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let QueryBounds {
+            leaf_width,
+            merkle_path,
+            ..
+        } = self.bounds;
+        Ok(match self.kind {
+            OpeningKind::Base => QueryOpening::Base {
+                values: next_field(&mut seq, 0, leaf_width, &self)?,
+                proof: next_field(&mut seq, 1, merkle_path, &self)?,
+            },
+            OpeningKind::Extension => QueryOpening::Extension {
+                values: next_field(&mut seq, 0, leaf_width, &self)?,
+                proof: next_field(&mut seq, 1, merkle_path, &self)?,
+            },
+        })
+    }
+}
+
+/// Seed deserializing a `T` whose variable-length collections hold at most `max_len`
+/// elements, at any depth.
+///
+/// Fixed-length tuples and arrays are not bounded, only sequences and maps are.
+struct Capped<T> {
+    max_len: usize,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Capped<T> {
+    const fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for Capped<T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+        T::deserialize(CappedDeserializer {
+            inner: deserializer,
+            max_len: self.max_len,
+        })
+    }
+}
+
+/// Wraps a seed so that the value it deserializes is bounded as well.
+struct CappedSeed<S> {
+    seed: S,
+    max_len: usize,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for CappedSeed<S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.seed.deserialize(CappedDeserializer {
+            inner: deserializer,
+            max_len: self.max_len,
+        })
+    }
+}
+
+struct CappedDeserializer<D> {
+    inner: D,
+    max_len: usize,
+}
+
+impl<D> CappedDeserializer<D> {
+    const fn visitor<V>(&self, visitor: V, enforce: bool) -> CappedVisitor<V> {
+        CappedVisitor {
+            inner: visitor,
+            max_len: self.max_len,
+            enforce,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let visitor = self.visitor(visitor, false);
+                self.inner.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for CappedDeserializer<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, true);
+        self.inner.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, true);
+        self.inner.deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor, false);
+        self.inner.deserialize_enum(name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Visitor bounding the sequences and maps it visits if `enforce` is set, and the nested
+/// collections in any case.
+struct CappedVisitor<V> {
+    inner: V,
+    max_len: usize,
+    enforce: bool,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident: $ty:ty)*) => {
+        $(
+            fn $method<E: de::Error>(self, value: $ty) -> Result<Self::Value, E> {
+                self.inner.$method(value)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for CappedVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool: bool visit_i8: i8 visit_i16: i16 visit_i32: i32 visit_i64: i64
+        visit_i128: i128 visit_u8: u8 visit_u16: u16 visit_u32: u32 visit_u64: u64
+        visit_u128: u128 visit_f32: f32 visit_f64: f64 visit_char: char visit_str: &str
+        visit_borrowed_str: &'de str visit_string: alloc::string::String visit_bytes: &[u8]
+        visit_borrowed_bytes: &'de [u8] visit_byte_buf: Vec<u8>
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        self.inner.visit_some(CappedDeserializer {
+            inner: deserializer,
+            max_len: self.max_len,
+        })
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        self.inner.visit_newtype_struct(CappedDeserializer {
+            inner: deserializer,
+            max_len: self.max_len,
+        })
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        if self.enforce {
+            check_len(seq.size_hint(), self.max_len)?;
+        }
+        self.inner.visit_seq(CappedAccess {
+            inner: seq,
+            max_len: self.max_len,
+            enforce: self.enforce,
+            count: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        if self.enforce {
+            check_len(map.size_hint(), self.max_len)?;
+        }
+        self.inner.visit_map(CappedAccess {
+            inner: map,
+            max_len: self.max_len,
+            enforce: self.enforce,
+            count: 0,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.inner.visit_enum(CappedAccess {
+            inner: data,
+            max_len: self.max_len,
+            enforce: false,
+            count: 0,
+        })
+    }
+}
+
+/// Sequence, map, enum or variant access bounding the values read through it.
+///
+/// Sequences and maps not announcing their length are counted as they are read.
+struct CappedAccess<A> {
+    inner: A,
+    max_len: usize,
+    enforce: bool,
+    count: usize,
+}
+
+impl<A> CappedAccess<A> {
+    const fn seed<S>(&self, seed: S) -> CappedSeed<S> {
+        CappedSeed {
+            seed,
+            max_len: self.max_len,
+        }
+    }
+
+    fn counted<E: de::Error, T>(&mut self, item: Option<T>) -> Result<Option<T>, E> {
+        if item.is_some() {
+            self.count += 1;
+            if self.enforce {
+                check_len(Some(self.count), self.max_len)?;
+            }
+        }
+        Ok(item)
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for CappedAccess<A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, Self::Error> {
+        let element = self.inner.next_element_seed(self.seed(seed))?;
+        self.counted(element)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for CappedAccess<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let key = self.inner.next_key_seed(self.seed(seed))?;
+        self.counted(key)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.inner.next_value_seed(self.seed(seed))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: EnumAccess<'de>> EnumAccess<'de> for CappedAccess<A> {
+    type Error = A::Error;
+    type Variant = CappedAccess<A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), Self::Error> {
+        let (value, variant) = self.inner.variant_seed(self.seed(seed))?;
+        Ok((
+            value,
+            CappedAccess {
+                inner: variant,
+                max_len: self.max_len,
+                enforce: false,
+                count: 0,
+            },
+        ))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for CappedAccess<A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        let seed = self.seed(seed);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.tuple_variant(
+            len,
+            CappedVisitor {
+                inner: visitor,
+                max_len: self.max_len,
+                enforce: false,
+            },
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.inner.struct_variant(
+            fields,
+            CappedVisitor {
+                inner: visitor,
+                max_len: self.max_len,
+                enforce: false,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use bincode::Options;
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::DuplexChallenger;
+    use p3_field::{PrimeCharacteristicRing, extension::BinomialExtensionField};
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_multilinear_util::poly::Poly;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+    use crate::{
+        parameters::{FoldingFactor, ProtocolParameters, SecurityAssumption},
+        whir::proof::SumcheckData,
+    };
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+    type MyMmcs = MerkleTreeMmcs<F, F, MyHash, MyCompress, 2, 8>;
+
+    type Proof = WhirProof<F, EF, MyMmcs>;
+
+    fn make_bounds() -> ProofBounds {
+        let perm = Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1));
+        let params = ProtocolParameters {
+            security_level: 32,
+            pow_bits: 0,
+            rs_domain_initial_reduction_factor: 1,
+            folding_factor: FoldingFactor::Constant(2),
+            mmcs: MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0),
+            soundness_type: SecurityAssumption::CapacityBound,
+            starting_log_inv_rate: 1,
+        };
+        let config = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(12, params);
+        ProofBounds::new(&config)
+    }
+
+    fn opening(bounds: &QueryBounds, extension: bool) -> QueryOpening<F, EF, Vec<[F; 8]>> {
+        let proof = vec![[F::ZERO; 8]; bounds.merkle_path];
+        if extension {
+            QueryOpening::Extension {
+                values: vec![EF::ZERO; bounds.leaf_width],
+                proof,
+            }
+        } else {
+            QueryOpening::Base {
+                values: vec![F::ZERO; bounds.leaf_width],
+                proof,
+            }
+        }
+    }
+
+    /// Builds a proof whose collections all reach their bounds.
+    fn largest_proof(bounds: &ProofBounds) -> Proof {
+        Proof {
+            claims: None,
+            config_fingerprint: [0; 32],
+            initial_commitment: None,
+            initial_ood_answers: vec![EF::ZERO; bounds.initial_ood_answers],
+            initial_sumcheck: SumcheckData::default(),
+            rounds: bounds
+                .rounds
+                .iter()
+                .enumerate()
+                .map(|(round_index, round)| WhirRoundProof {
+                    commitment: None,
+                    ood_answers: vec![EF::ZERO; round.ood_answers],
+                    pow_witness: F::ZERO,
+                    queries: (0..round.queries.queries)
+                        .map(|_| opening(&round.queries, round_index > 0))
+                        .collect(),
+                    sumcheck: SumcheckData::default(),
+                })
+                .collect(),
+            final_poly: Some(Poly::new(vec![EF::ZERO; bounds.final_poly])),
+            final_pow_witness: F::ZERO,
+            final_queries: (0..bounds.final_queries.queries)
+                .map(|_| opening(&bounds.final_queries, !bounds.rounds.is_empty()))
+                .collect(),
+            final_sumcheck: None,
+        }
+    }
+
+    fn deserialize(bounds: &ProofBounds, bytes: &[u8]) -> bincode::Result<Proof> {
+        let options = bincode::DefaultOptions::new().with_fixint_encoding();
+        Proof::deserialize_with_bounds(
+            bounds,
+            &mut bincode::Deserializer::from_slice(bytes, options),
+        )
+    }
+
+    #[test]
+    fn test_proof_within_bounds_roundtrips() {
+        let bounds = make_bounds();
+        assert!(!bounds.rounds.is_empty());
+
+        let bytes = bincode::serialize(&largest_proof(&bounds)).unwrap();
+        let proof = deserialize(&bounds, &bytes).unwrap();
+        assert_eq!(bincode::serialize(&proof).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_extra_round_is_rejected() {
+        let bounds = make_bounds();
+        let mut proof = largest_proof(&bounds);
+        proof.rounds.push(proof.rounds[0].clone());
+
+        let bytes = bincode::serialize(&proof).unwrap();
+        assert!(deserialize(&bounds, &bytes).is_err());
+    }
+
+    #[test]
+    fn test_oversized_collections_are_rejected() {
+        let bounds = make_bounds();
+        let tampers: [fn(&mut Proof); 5] = [
+            |proof| proof.initial_ood_answers.push(EF::ZERO),
+            |proof| proof.rounds[0].ood_answers.push(EF::ZERO),
+            |proof| {
+                let query = proof.final_queries[0].clone();
+                proof.final_queries.push(query);
+            },
+            |proof| match &mut proof.rounds[0].queries[0] {
+                QueryOpening::Base { values, .. } => values.push(F::ZERO),
+                QueryOpening::Extension { values, .. } => values.push(EF::ZERO),
+            },
+            |proof| match &mut proof.final_queries[0] {
+                QueryOpening::Base { proof, .. } | QueryOpening::Extension { proof, .. } => {
+                    proof.push([F::ZERO; 8]);
+                }
+            },
+        ];
+
+        for (i, tamper) in tampers.into_iter().enumerate() {
+            let mut proof = largest_proof(&bounds);
+            tamper(&mut proof);
+            let bytes = bincode::serialize(&proof).unwrap();
+            assert!(deserialize(&bounds, &bytes).is_err(), "tamper {i} accepted");
+        }
+    }
+
+    #[test]
+    fn test_forged_length_prefix_fails_before_allocating() {
+        let bounds = make_bounds();
+        let mut bytes = bincode::serialize(&largest_proof(&bounds)).unwrap();
+
+        // `claims` and `initial_commitment` are `None` (one byte each) around the 32-byte
+        // fingerprint, then comes the length prefix of `initial_ood_answers`.
+        let offset = 1 + 32 + 1;
+        bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        let err = deserialize(&bounds, &bytes).unwrap_err();
+        assert!(err.to_string().contains("exceeds the bound"), "{err}");
+    }
+}
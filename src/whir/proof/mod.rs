@@ -1,20 +1,40 @@
 use alloc::vec::Vec;
 
-use p3_commit::Mmcs;
-use p3_multilinear_util::poly::Poly;
+use p3_field::Field;
+use p3_multilinear_util::{point::Point, poly::Poly};
 use serde::{Deserialize, Serialize};
 
-use crate::parameters::ProtocolParameters;
 pub use crate::sumcheck::SumcheckData;
+use crate::{
+    constraints::statement::EqStatement,
+    parameters::ProtocolParameters,
+    whir::{
+        config::ConfigFingerprint,
+        mmcs::{RoundCommitment, RoundProof, WhirMmcs},
+    },
+};
+
+pub mod bounded;
 
 /// Complete WHIR proof
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(bound(
-    serialize = "F: Serialize, EF: Serialize, MT::Commitment: Serialize, MT::Proof: Serialize",
-    deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, MT::Commitment: Deserialize<'de>, MT::Proof: Deserialize<'de>"
+    serialize = "F: Serialize, EF: Serialize, MT::Commitment: Serialize, MT::Proof: Serialize, \
+                 RoundCommitment<F, MT>: Serialize, RoundProof<F, MT>: Serialize",
+    deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, MT::Commitment: Deserialize<'de>, \
+                   MT::Proof: Deserialize<'de>, RoundCommitment<F, MT>: Deserialize<'de>, \
+                   RoundProof<F, MT>: Deserialize<'de>"
 ))]
-// TODO: add initial claims?
-pub struct WhirProof<F: Send + Sync + Clone, EF, MT: Mmcs<F>> {
+pub struct WhirProof<F: Send + Sync + Clone, EF, MT: WhirMmcs<F>> {
+    /// Evaluation claims proven by this proof, if embedded
+    ///
+    /// When present, the proof is self-describing: it can be checked with
+    /// `Verifier::verify_standalone` without supplying the statement separately.
+    pub claims: Option<InitialClaims<EF>>,
+
+    /// Fingerprint of the configuration the proof was generated with
+    pub config_fingerprint: ConfigFingerprint,
+
     /// Initial polynomial commitment (Merkle root)
     pub initial_commitment: Option<MT::Commitment>,
 
@@ -24,7 +44,7 @@ pub struct WhirProof<F: Send + Sync + Clone, EF, MT: Mmcs<F>> {
     /// Initial phase data - captures the protocol variant
     pub initial_sumcheck: SumcheckData<F, EF>,
 
-    /// One proof per WHIR round
+    /// One proof per WHIR round, committed with the round MMCS of `MT`
     pub rounds: Vec<WhirRoundProof<F, EF, MT>>,
 
     /// Final polynomial evaluations
@@ -34,15 +54,19 @@ pub struct WhirProof<F: Send + Sync + Clone, EF, MT: Mmcs<F>> {
     pub final_pow_witness: F,
 
     /// Final round query openings
-    pub final_queries: Vec<QueryOpening<F, EF, MT::Proof>>,
+    pub final_queries: Vec<QueryOpening<F, EF, MT::Proof, RoundProof<F, MT>>>,
 
     /// Final sumcheck (if final_sumcheck_rounds > 0)
     pub final_sumcheck: Option<SumcheckData<F, EF>>,
 }
 
-impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> Default for WhirProof<F, EF, MT> {
+impl<F: Default + Send + Sync + Clone, EF: Default, MT: WhirMmcs<F>> Default
+    for WhirProof<F, EF, MT>
+{
     fn default() -> Self {
         Self {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -58,12 +82,14 @@ impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> Default for Whi
 /// Data for a single WHIR round
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(bound(
-    serialize = "F: Serialize, EF: Serialize, MT::Commitment: Serialize, MT::Proof: Serialize",
-    deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, MT::Commitment: Deserialize<'de>, MT::Proof: Deserialize<'de>"
+    serialize = "F: Serialize, EF: Serialize, MT::Proof: Serialize, \
+                 RoundCommitment<F, MT>: Serialize, RoundProof<F, MT>: Serialize",
+    deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, MT::Proof: Deserialize<'de>, \
+                   RoundCommitment<F, MT>: Deserialize<'de>, RoundProof<F, MT>: Deserialize<'de>"
 ))]
-pub struct WhirRoundProof<F: Send + Sync + Clone, EF, MT: Mmcs<F>> {
+pub struct WhirRoundProof<F: Send + Sync + Clone, EF, MT: WhirMmcs<F>> {
     /// Round commitment (Merkle root)
-    pub commitment: Option<MT::Commitment>,
+    pub commitment: Option<RoundCommitment<F, MT>>,
 
     /// OOD evaluations for this round
     pub ood_answers: Vec<EF>,
@@ -71,14 +97,15 @@ pub struct WhirRoundProof<F: Send + Sync + Clone, EF, MT: Mmcs<F>> {
     /// PoW witness after commitment
     pub pow_witness: F,
 
-    /// STIR query openings
-    pub queries: Vec<QueryOpening<F, EF, MT::Proof>>,
+    /// STIR query openings, of the initial commitment in the first round and of the
+    /// previous round commitment afterwards
+    pub queries: Vec<QueryOpening<F, EF, MT::Proof, RoundProof<F, MT>>>,
 
     /// Sumcheck data for this round
     pub sumcheck: SumcheckData<F, EF>,
 }
 
-impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> Default
+impl<F: Default + Send + Sync + Clone, EF: Default, MT: WhirMmcs<F>> Default
     for WhirRoundProof<F, EF, MT>
 {
     fn default() -> Self {
@@ -93,15 +120,18 @@ impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> Default
 }
 
 /// Query opening
+///
+/// Base field openings are authenticated by a `Proof` of the initial commitment, extension
+/// field openings by an `ExtProof` of a round commitment.
+///
+/// Externally tagged, so that non-self-describing formats such as bincode can deserialize it.
 #[derive(Serialize, Deserialize, Clone, Debug)]
-#[serde(
-    bound(
-        serialize = "F: Serialize, EF: Serialize, Proof: Serialize",
-        deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, Proof: Deserialize<'de>"
-    ),
-    tag = "type"
-)]
-pub enum QueryOpening<F, EF, Proof> {
+#[serde(bound(
+    serialize = "F: Serialize, EF: Serialize, Proof: Serialize, ExtProof: Serialize",
+    deserialize = "F: Deserialize<'de>, EF: Deserialize<'de>, Proof: Deserialize<'de>, \
+                   ExtProof: Deserialize<'de>"
+))]
+pub enum QueryOpening<F, EF, Proof, ExtProof = Proof> {
     /// Base field query (round_index == 0)
     #[serde(rename = "base")]
     Base {
@@ -116,11 +146,61 @@ pub enum QueryOpening<F, EF, Proof> {
         /// Merkle leaf values in EF
         values: Vec<EF>,
         /// Merkle authentication path
-        proof: Proof,
+        proof: ExtProof,
     },
 }
 
-impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> WhirProof<F, EF, MT> {
+/// Evaluation claims about the committed polynomial.
+///
+/// This is the serializable form of the verifier's `EqStatement`: the proof asserts that
+/// the committed multilinear polynomial `f` satisfies `f(points[i]) = evaluations[i]`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InitialClaims<EF> {
+    /// Number of variables of the committed polynomial
+    pub num_variables: usize,
+
+    /// Evaluation points, one per claim
+    pub points: Vec<Vec<EF>>,
+
+    /// Claimed evaluations, in the same order as `points`
+    pub evaluations: Vec<EF>,
+}
+
+impl<EF: Field> InitialClaims<EF> {
+    /// Captures the claims of a statement over `num_variables` variables.
+    pub fn new(num_variables: usize, statement: &EqStatement<EF>) -> Self {
+        let (points, evaluations) = statement
+            .iter()
+            .map(|(point, &eval)| (point.as_slice().to_vec(), eval))
+            .unzip();
+        Self {
+            num_variables,
+            points,
+            evaluations,
+        }
+    }
+
+    /// Returns `true` if every point has `num_variables` coordinates and there is exactly one
+    /// evaluation per point.
+    pub fn is_well_formed(&self) -> bool {
+        self.points.len() == self.evaluations.len()
+            && self
+                .points
+                .iter()
+                .all(|point| point.len() == self.num_variables)
+    }
+
+    /// Rebuilds the equality statement expressing these claims.
+    pub fn to_statement(&self) -> EqStatement<EF> {
+        let mut statement = EqStatement::initialize(self.num_variables);
+        for (point, &eval) in self.points.iter().zip(&self.evaluations) {
+            statement.add_evaluated_constraint(Point::new(point.clone()), eval);
+        }
+        statement
+    }
+}
+
+impl<F: Default + Send + Sync + Clone, EF: Default, MT: WhirMmcs<F>> WhirProof<F, EF, MT> {
     /// Create a new WhirProof from protocol parameters and configuration
     ///
     /// This initializes an empty proof structure with appropriate capacity allocations
@@ -148,6 +228,8 @@ impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> WhirProof<F, EF
             .queries(protocol_security_level, params.starting_log_inv_rate);
 
         Self {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -160,7 +242,7 @@ impl<F: Default + Send + Sync + Clone, EF: Default, MT: Mmcs<F>> WhirProof<F, EF
     }
 }
 
-impl<F: Clone + Send + Sync + Default, EF, MT: Mmcs<F>> WhirProof<F, EF, MT> {
+impl<F: Clone + Send + Sync + Default, EF, MT: WhirMmcs<F>> WhirProof<F, EF, MT> {
     /// Extract the PoW witness after the commitment at the given round index
     ///
     /// Returns the PoW witness from the round at the given index.
@@ -324,6 +406,8 @@ mod tests {
 
         // Create a proof with one round containing a PoW witness
         let proof: WhirProof<F, EF, MyMmcs> = WhirProof {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -354,6 +438,8 @@ mod tests {
     fn test_get_pow_after_commitment_invalid_round() {
         // Create a proof with one round
         let proof: WhirProof<F, EF, MyMmcs> = WhirProof {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -469,6 +555,8 @@ mod tests {
     fn test_set_final_sumcheck_data() {
         // Create a proof with no rounds
         let mut proof: WhirProof<F, EF, MyMmcs> = WhirProof {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -499,6 +587,8 @@ mod tests {
     fn test_set_sumcheck_data_at_round() {
         // Create a proof with two rounds
         let mut proof: WhirProof<F, EF, MyMmcs> = WhirProof {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -536,6 +626,8 @@ mod tests {
     fn test_set_sumcheck_data_at_no_rounds_panics() {
         // Create a proof with no rounds
         let mut proof: WhirProof<F, EF, MyMmcs> = WhirProof {
+            claims: None,
+            config_fingerprint: ConfigFingerprint::default(),
             initial_commitment: None,
             initial_ood_answers: Vec::new(),
             initial_sumcheck: SumcheckData::default(),
@@ -549,4 +641,33 @@ mod tests {
         // Try to set sumcheck data at index 0 with no rounds - should panic
         proof.set_sumcheck_data_at(SumcheckData::default(), 0);
     }
+
+    #[test]
+    fn test_initial_claims_roundtrip() {
+        let num_variables = 3;
+
+        // Build a statement with two evaluation claims.
+        let mut statement = EqStatement::initialize(num_variables);
+        statement.add_evaluated_constraint(
+            Point::new(vec![EF::from_u64(1), EF::from_u64(2), EF::from_u64(3)]),
+            EF::from_u64(10),
+        );
+        statement.add_evaluated_constraint(
+            Point::new(vec![EF::from_u64(4), EF::from_u64(5), EF::from_u64(6)]),
+            EF::from_u64(20),
+        );
+
+        let claims = InitialClaims::new(num_variables, &statement);
+        assert!(claims.is_well_formed());
+        assert_eq!(claims.points.len(), 2);
+        assert_eq!(claims.evaluations, vec![EF::from_u64(10), EF::from_u64(20)]);
+
+        // Converting back yields the original statement.
+        assert_eq!(claims.to_statement(), statement);
+
+        // A point with the wrong number of coordinates is rejected.
+        let mut malformed = claims;
+        malformed.points[0].pop();
+        assert!(!malformed.is_well_formed());
+    }
 }
@@ -0,0 +1,310 @@
+//! Property-based tests of the full protocol over randomly generated configurations.
+//!
+//! Failing cases are shrunk by proptest towards the smallest folding schedule, number of
+//! variables and reduction factor that still fail.
+
+extern crate std;
+
+use alloc::vec::Vec;
+
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::{Field, PrimeCharacteristicRing, extension::BinomialExtensionField};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use proptest::{prelude::*, sample::Index};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+
+use crate::{
+    constraints::statement::EqStatement,
+    fiat_shamir::domain_separator::DomainSeparator,
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        config::domain_separator,
+        proof::{QueryOpening, SumcheckData, WhirProof},
+        prover::Prover,
+        verifier::{Verifier, errors::VerifierError},
+    },
+};
+
+type F = BabyBear;
+type EF = BinomialExtensionField<F, 4>;
+type Perm = Poseidon2BabyBear<16>;
+
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+
+type PackedF = <F as Field>::Packing;
+type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
+
+type Proof = WhirProof<F, EF, MyMmcs>;
+
+/// Largest number of variables generated, to keep each case fast.
+const MAX_NUM_VARIABLES: usize = 12;
+
+/// A protocol configuration together with the seed of its witness.
+#[derive(Debug, Clone)]
+struct ProtocolCase {
+    num_variables: usize,
+    folding_factor: FoldingFactor,
+    rs_domain_initial_reduction_factor: usize,
+    starting_log_inv_rate: usize,
+    soundness_type: SecurityAssumption,
+    pow_bits: usize,
+    num_points: usize,
+    sumcheck_strategy: SumcheckStrategy,
+    seed: u64,
+}
+
+fn arb_folding_factor() -> impl Strategy<Value = FoldingFactor> {
+    prop_oneof![
+        (1..=4usize).prop_map(FoldingFactor::Constant),
+        (1..=5usize, 1..=3usize)
+            .prop_map(|(first, rest)| FoldingFactor::ConstantFromSecondRound(first, rest)),
+    ]
+}
+
+/// Generates valid configurations.
+///
+/// As in `test_whir_end_to_end`, the first folding step must absorb the initial domain
+/// reduction and the polynomial must have enough variables for the first fold.
+fn arb_protocol_case() -> impl Strategy<Value = ProtocolCase> {
+    arb_folding_factor().prop_flat_map(|folding_factor| {
+        let first_fold = folding_factor.at_round(0);
+        (
+            first_fold..=MAX_NUM_VARIABLES.min(3 * first_fold),
+            1..=first_fold.min(3),
+            1..=2usize,
+            prop_oneof![
+                Just(SecurityAssumption::JohnsonBound),
+                Just(SecurityAssumption::CapacityBound),
+                Just(SecurityAssumption::UniqueDecoding),
+            ],
+            prop_oneof![Just(0usize), Just(5usize)],
+            0..=2usize,
+            prop_oneof![Just(SumcheckStrategy::Classic), Just(SumcheckStrategy::Svo)],
+            any::<u64>(),
+        )
+            .prop_map(
+                move |(
+                    num_variables,
+                    rs_domain_initial_reduction_factor,
+                    starting_log_inv_rate,
+                    soundness_type,
+                    pow_bits,
+                    num_points,
+                    sumcheck_strategy,
+                    seed,
+                )| ProtocolCase {
+                    num_variables,
+                    folding_factor,
+                    rs_domain_initial_reduction_factor,
+                    starting_log_inv_rate,
+                    soundness_type,
+                    pow_bits,
+                    num_points,
+                    sumcheck_strategy,
+                    seed,
+                },
+            )
+    })
+}
+
+/// An honest proof for a [`ProtocolCase`], with what is needed to verify it.
+struct ProvenCase {
+    params: WhirConfig<EF, F, MyMmcs, MyChallenger>,
+    challenger: MyChallenger,
+    domainsep: DomainSeparator<EF, F>,
+    statement: EqStatement<EF>,
+    proof: Proof,
+}
+
+impl ProvenCase {
+    fn new(case: &ProtocolCase) -> Self {
+        let mut rng = SmallRng::seed_from_u64(case.seed);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()), 0);
+
+        let whir_params = ProtocolParameters {
+            security_level: 32,
+            pow_bits: case.pow_bits,
+            rs_domain_initial_reduction_factor: case.rs_domain_initial_reduction_factor,
+            folding_factor: case.folding_factor,
+            mmcs,
+            soundness_type: case.soundness_type,
+            starting_log_inv_rate: case.starting_log_inv_rate,
+        };
+        let params =
+            WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(case.num_variables, whir_params.clone());
+
+        let polynomial = Poly::new((0..1 << case.num_variables).map(|_| rng.random()).collect());
+        let mut initial_statement = params.initial_statement(polynomial, case.sumcheck_strategy);
+        for _ in 0..case.num_points {
+            let _ = initial_statement.evaluate(&Point::rand(&mut rng, case.num_variables));
+        }
+        let statement = initial_statement.normalize();
+
+        let mut domainsep = domain_separator(&params);
+        domainsep.commit_statement::<_, _, 8>(&params);
+        domainsep.add_whir_proof::<_, _, 8>(&params);
+        let challenger = MyChallenger::new(perm);
+
+        let dft = Radix2DFTSmallBatch::<F>::default();
+        let (root, prover_data) = CommitmentWriter::new(&params)
+            .commit(&dft, &initial_statement.poly)
+            .unwrap();
+        let mut proof = Proof::from_protocol_parameters(&whir_params, case.num_variables);
+        let mut prover_challenger = challenger.clone();
+        domainsep.observe_domain_separator(&mut prover_challenger);
+        Prover(&params)
+            .prove(
+                &dft,
+                &mut proof,
+                &mut prover_challenger,
+                &mut initial_statement,
+                root,
+                &prover_data,
+            )
+            .unwrap();
+
+        Self {
+            params,
+            challenger,
+            domainsep,
+            statement,
+            proof,
+        }
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<Point<EF>, VerifierError> {
+        let mut challenger = self.challenger.clone();
+        self.domainsep.observe_domain_separator(&mut challenger);
+        let parsed_commitment =
+            CommitmentReader::new(&self.params).parse_commitment::<F, 8>(proof, &mut challenger)?;
+        Verifier::new(&self.params).verify(
+            proof,
+            &mut challenger,
+            &parsed_commitment,
+            self.statement.clone(),
+        )
+    }
+}
+
+/// A single field element of a proof that the verifier must bind.
+enum Slot<'a> {
+    Base(&'a mut F),
+    Extension(&'a mut EF),
+    /// The final polynomial, perturbed at a coefficient chosen with the slot.
+    FinalPoly(&'a mut Poly<EF>),
+}
+
+impl Slot<'_> {
+    fn perturb(&mut self, index: Index) {
+        match self {
+            Slot::Base(value) => **value += F::ONE,
+            Slot::Extension(value) => **value += EF::ONE,
+            Slot::FinalPoly(poly) => {
+                let mut evals = poly.as_slice().to_vec();
+                let len = evals.len();
+                evals[index.index(len)] += EF::ONE;
+                **poly = Poly::new(evals);
+            }
+        }
+    }
+}
+
+/// Collects every element of the proof that the verifier binds: OOD answers, sumcheck
+/// coefficients, opened leaf values and Merkle siblings, PoW witnesses and the final
+/// polynomial.
+///
+/// Round and final PoW witnesses are only checked when their round grinds, so they are
+/// left out for rounds without PoW.
+fn perturbable_slots<'a>(
+    params: &WhirConfig<EF, F, MyMmcs, MyChallenger>,
+    proof: &'a mut Proof,
+) -> Vec<Slot<'a>> {
+    fn openings<'a>(
+        queries: &'a mut [QueryOpening<F, EF, Vec<[F; 8]>>],
+    ) -> impl Iterator<Item = Slot<'a>> {
+        queries.iter_mut().flat_map(|query| -> Vec<Slot<'a>> {
+            let (leaves, siblings): (Vec<_>, _) = match query {
+                QueryOpening::Base { values, proof } => {
+                    (values.iter_mut().map(Slot::Base).collect(), proof)
+                }
+                QueryOpening::Extension { values, proof } => {
+                    (values.iter_mut().map(Slot::Extension).collect(), proof)
+                }
+            };
+            leaves
+                .into_iter()
+                .chain(siblings.iter_mut().flatten().map(Slot::Base))
+                .collect()
+        })
+    }
+
+    fn sumcheck(data: &mut SumcheckData<F, EF>) -> impl Iterator<Item = Slot<'_>> {
+        data.polynomial_evaluations
+            .iter_mut()
+            .flatten()
+            .map(Slot::Extension)
+            .chain(data.pow_witnesses.iter_mut().map(Slot::Base))
+    }
+
+    let mut slots: Vec<_> = proof
+        .initial_ood_answers
+        .iter_mut()
+        .map(Slot::Extension)
+        .collect();
+    slots.extend(sumcheck(&mut proof.initial_sumcheck));
+    for (round, round_params) in proof.rounds.iter_mut().zip(&params.round_parameters) {
+        slots.extend(round.ood_answers.iter_mut().map(Slot::Extension));
+        slots.extend(sumcheck(&mut round.sumcheck));
+        slots.extend(openings(&mut round.queries));
+        if round_params.pow_bits > 0 {
+            slots.push(Slot::Base(&mut round.pow_witness));
+        }
+    }
+    slots.extend(openings(&mut proof.final_queries));
+    if params.final_pow_bits > 0 {
+        slots.push(Slot::Base(&mut proof.final_pow_witness));
+    }
+    if let Some(final_sumcheck) = &mut proof.final_sumcheck {
+        slots.extend(sumcheck(final_sumcheck));
+    }
+    if let Some(final_poly) = &mut proof.final_poly {
+        slots.push(Slot::FinalPoly(final_poly));
+    }
+    slots
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn prop_honest_proofs_verify(case in arb_protocol_case()) {
+        let proven = ProvenCase::new(&case);
+        prop_assert!(proven.verify(&proven.proof).is_ok());
+    }
+
+    #[test]
+    fn prop_single_element_perturbations_are_rejected(
+        case in arb_protocol_case(),
+        slot in any::<Index>(),
+    ) {
+        let proven = ProvenCase::new(&case);
+
+        let mut proof = proven.proof.clone();
+        let mut slots = perturbable_slots(&proven.params, &mut proof);
+        prop_assume!(!slots.is_empty());
+        let len = slots.len();
+        slots[slot.index(len)].perturb(slot);
+
+        prop_assert!(proven.verify(&proof).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! Prover error types.
+
+use thiserror::Error;
+
+use crate::{fiat_shamir::errors::FiatShamirError, whir::prover::pow::GrindingError};
+
+/// Error returned by the WHIR prover.
+#[derive(Error, Debug)]
+pub enum ProverError {
+    /// The transcript failed to produce a challenge.
+    #[error(transparent)]
+    FiatShamir(#[from] FiatShamirError),
+
+    /// The folding schedule does not fold exactly the variables of the configuration.
+    #[error("the folding schedule folds {folded} variables, expected {num_variables}")]
+    InvalidParameters { num_variables: usize, folded: usize },
+
+    /// The polynomial does not have the number of variables of the configuration.
+    #[error("polynomial over {actual} variables, expected {expected}")]
+    PolynomialSize { expected: usize, actual: usize },
+
+    /// The proof-of-work preceding the STIR queries of a round stopped without a witness.
+    ///
+    /// `round` equals the number of rounds for the final round.
+    #[error("round {round}: {source}")]
+    Pow {
+        round: usize,
+        #[source]
+        source: GrindingError,
+    },
+
+    /// A proof-of-work of the sumcheck of a round stopped without a witness.
+    ///
+    /// The initial sumcheck belongs to round 0, the final one to the number of rounds.
+    #[error("sumcheck of round {round}: {source}")]
+    SumcheckPow {
+        round: usize,
+        #[source]
+        source: GrindingError,
+    },
+}
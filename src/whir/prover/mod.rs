@@ -1,17 +1,21 @@
 use alloc::vec::Vec;
 use core::ops::Deref;
 
+use errors::ProverError;
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::{ExtensionMmcs, Mmcs};
 use p3_dft::TwoAdicSubgroupDft;
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::{
     Matrix,
-    dense::{DenseMatrix, RowMajorMatrixView},
+    dense::{DenseMatrix, RowMajorMatrix, RowMajorMatrixView, RowMajorMatrixViewMut},
     extension::FlatMatrixView,
 };
+use p3_maybe_rayon::prelude::*;
 use p3_multilinear_util::{point::Point, poly::Poly};
+use pow::Grinder;
 use round_state::RoundState;
+use serde::Serialize;
 use tracing::{info_span, instrument};
 
 use crate::{
@@ -19,19 +23,60 @@ use crate::{
         Constraint,
         statement::{EqStatement, SelectStatement, initial::InitialStatement},
     },
-    fiat_shamir::errors::FiatShamirError,
     parameters::WhirConfig,
     whir::{
+        config::WhirConfigExt,
+        mmcs::{RoundCommitment, RoundMmcs, RoundProof, WhirMmcs},
         proof::{QueryOpening, SumcheckData, WhirProof},
         utils::get_challenge_stir_queries,
     },
 };
 
+pub mod errors;
+pub mod pow;
 pub mod round_state;
+#[cfg(test)]
+mod tests;
 
 pub type Proof<W, const DIGEST_ELEMS: usize> = Vec<Vec<[W; DIGEST_ELEMS]>>;
 pub type Leafs<F> = Vec<Vec<F>>;
 
+/// Tree committing to the folded polynomial of a round.
+pub type RoundProverData<F, EF, MT> =
+    <RoundMmcs<F, MT> as Mmcs<F>>::ProverData<FlatMatrixView<F, EF, DenseMatrix<EF>>>;
+
+/// Tree of the initial commitment, borrowed from the caller or owned by the prover.
+///
+/// An owned tree is released as soon as the queries of the first round are answered.
+#[derive(Debug)]
+pub(crate) enum InitialProverData<'a, T> {
+    /// Tree kept by the caller, e.g. to open the commitment again.
+    Borrowed(&'a T),
+    /// Tree handed over to the prover.
+    Owned(T),
+    /// Tree released after answering its queries.
+    Released,
+}
+
+impl<T> InitialProverData<'_, T> {
+    /// Returns the tree.
+    ///
+    /// # Panics
+    /// Panics if the tree was released.
+    fn get(&self) -> &T {
+        match self {
+            Self::Borrowed(data) => data,
+            Self::Owned(data) => data,
+            Self::Released => panic!("initial tree queried after its release"),
+        }
+    }
+
+    /// Frees the tree if the prover owns it.
+    fn release(&mut self) {
+        *self = Self::Released;
+    }
+}
+
 #[derive(Debug)]
 pub struct Prover<'a, EF, F, MT, Challenger>(
     /// Reference to the protocol configuration shared across prover components.
@@ -58,7 +103,12 @@ where
     F: TwoAdicField + Ord,
     EF: ExtensionField<F> + TwoAdicField,
     Challenger: FieldChallenger<F> + GrindingChallenger<Witness = F>,
-    MT: Mmcs<F>,
+    MT: WhirMmcs<F> + Sync,
+    MT::ProverData<DenseMatrix<F>>: Sync,
+    MT::Proof: Send,
+    RoundMmcs<F, MT>: Sync,
+    RoundProverData<F, EF, MT>: Sync,
+    RoundProof<F, MT>: Send,
 {
     /// Validates that the total number of variables expected by the prover configuration
     /// matches the number implied by the folding schedule and the final rounds.
@@ -66,11 +116,19 @@ where
     /// This ensures that the recursive folding in the sumcheck protocol terminates
     /// precisely at the expected number of final variables.
     ///
-    /// # Returns
-    /// `true` if the parameter configuration is consistent, `false` otherwise.
-    const fn validate_parameters(&self) -> bool {
-        self.0.num_variables
-            == self.0.folding_factor.total_number(self.0.n_rounds()) + self.0.final_sumcheck_rounds
+    /// # Errors
+    /// Returns [`ProverError::InvalidParameters`] if the configuration is inconsistent.
+    const fn validate_parameters(&self) -> Result<(), ProverError> {
+        let folded =
+            self.0.folding_factor.total_number(self.0.n_rounds()) + self.0.final_sumcheck_rounds;
+        if self.0.num_variables == folded {
+            Ok(())
+        } else {
+            Err(ProverError::InvalidParameters {
+                num_variables: self.0.num_variables,
+                folded,
+            })
+        }
     }
 
     /// Executes the full WHIR prover protocol to produce the proof.
@@ -80,6 +138,7 @@ where
     /// a proof that the witness satisfies the statement.
     ///
     /// The proof includes:
+    /// - The initial commitment and the answers to its out-of-domain (OOD) queries
     /// - Merkle authentication paths for each round's polynomial commitments
     /// - Final evaluations of the public linear statement constraints at a random point
     ///
@@ -87,72 +146,236 @@ where
     /// - `dft`: A DFT backend used for evaluations
     /// - `proof`: Mutable proof structure to store the generated proof data
     /// - `challenger`: Mutable Fiat-Shamir challenger for transcript management
-    /// - `statement`: The public input, consisting of linear or nonlinear constraints
-    /// - `witness`: The private witness satisfying the constraints, including committed values
+    /// - `statement`: The public input, consisting of linear or nonlinear constraints.
+    ///   The OOD constraints of the commitment are appended to it.
+    /// - `commitment`: The Merkle root returned by `CommitmentWriter::commit`
+    /// - `prover_data`: The Merkle tree returned by `CommitmentWriter::commit`
     ///
+    /// The prover data is only borrowed, so a single commitment can be opened several times,
+    /// each time with its own statement and transcript, without recomputing the DFT and tree.
     ///
     /// # Errors
     /// Returns an error if the witness or statement are invalid, or if a round fails.
-    #[instrument(skip_all)]
     pub fn prove<Dft>(
         &self,
         dft: &Dft,
         proof: &mut WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
-        statement: &InitialStatement<F, EF>,
-        prover_data: MT::ProverData<DenseMatrix<F>>,
-    ) -> Result<(), FiatShamirError>
+        statement: &mut InitialStatement<F, EF>,
+        commitment: MT::Commitment,
+        prover_data: &MT::ProverData<DenseMatrix<F>>,
+    ) -> Result<(), ProverError>
     where
         Dft: TwoAdicSubgroupDft<F>,
-        Challenger: CanObserve<MT::Commitment>,
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
     {
-        // Validate parameters
-        assert!(self.validate_parameters(), "Invalid prover parameters");
-
-        // Initialize the round state with inputs and initial polynomial data
-        let mut round_state = RoundState::initialize_first_round_state(
-            &mut proof.initial_sumcheck,
+        self.prove_with_grinder(
+            &Grinder::default(),
+            dft,
+            proof,
             challenger,
             statement,
+            commitment,
             prover_data,
-            self.folding_factor.at_round(0),
-            self.starting_folding_pow_bits,
-        )?;
+        )
+    }
+
+    /// Proves as [`Self::prove`], grinding the proofs-of-work with `grinder`.
+    ///
+    /// A grinder with a cancellation flag or a time budget bounds the time spent on every
+    /// proof-of-work of the proof, those of the sumchecks included.
+    ///
+    /// # Errors
+    /// Returns an error if a round fails, or if `grinder` stops before finding a witness.
+    #[instrument(skip_all)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_grinder<Dft>(
+        &self,
+        grinder: &Grinder,
+        dft: &Dft,
+        proof: &mut WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        statement: &mut InitialStatement<F, EF>,
+        commitment: MT::Commitment,
+        prover_data: &MT::ProverData<DenseMatrix<F>>,
+    ) -> Result<(), ProverError>
+    where
+        Dft: TwoAdicSubgroupDft<F>,
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
+    {
+        self.prove_from(
+            grinder,
+            dft,
+            proof,
+            challenger,
+            statement,
+            commitment,
+            InitialProverData::Borrowed(prover_data),
+        )
+    }
+
+    /// Proves as [`Self::prove_with_grinder`], taking ownership of the tree of the commitment.
+    ///
+    /// The tree is freed as soon as the queries of the first round are answered, instead of
+    /// living until the end of the proof, which lowers the peak memory of the later rounds.
+    /// The commitment cannot be opened again.
+    ///
+    /// # Errors
+    /// Same as [`Self::prove_with_grinder`].
+    #[instrument(skip_all)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_owned<Dft>(
+        &self,
+        grinder: &Grinder,
+        dft: &Dft,
+        proof: &mut WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        statement: &mut InitialStatement<F, EF>,
+        commitment: MT::Commitment,
+        prover_data: MT::ProverData<DenseMatrix<F>>,
+    ) -> Result<(), ProverError>
+    where
+        Dft: TwoAdicSubgroupDft<F>,
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
+    {
+        self.prove_from(
+            grinder,
+            dft,
+            proof,
+            challenger,
+            statement,
+            commitment,
+            InitialProverData::Owned(prover_data),
+        )
+    }
+
+    /// Runs the protocol on the tree of the commitment, borrowed or owned.
+    #[allow(clippy::too_many_arguments)]
+    fn prove_from<Dft>(
+        &self,
+        grinder: &Grinder,
+        dft: &Dft,
+        proof: &mut WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        statement: &mut InitialStatement<F, EF>,
+        commitment: MT::Commitment,
+        prover_data: InitialProverData<'_, MT::ProverData<DenseMatrix<F>>>,
+    ) -> Result<(), ProverError>
+    where
+        Dft: TwoAdicSubgroupDft<F>,
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
+    {
+        // Validate parameters and witness
+        self.validate_parameters()?;
+        let num_variables = statement.poly.num_vars();
+        if num_variables != self.num_variables {
+            return Err(ProverError::PolynomialSize {
+                expected: self.num_variables,
+                actual: num_variables,
+            });
+        }
+
+        // Bind the commitment to the transcript and answer its OOD queries
+        self.observe_commitment(proof, challenger, statement, commitment);
+
+        // Initialize the round state with inputs and initial polynomial data
+        let mut round_state = grinder
+            .run(challenger, |challenger| {
+                RoundState::initialize_first_round_state(
+                    &mut proof.initial_sumcheck,
+                    challenger,
+                    statement,
+                    prover_data,
+                    self.folding_factor.at_round(0),
+                    self.starting_folding_pow_bits,
+                )
+            })
+            .map_err(|source| ProverError::SumcheckPow { round: 0, source })??;
 
         // Run the WHIR protocol round-by-round
         for round in 0..=self.n_rounds() {
-            self.round(dft, round, proof, challenger, &mut round_state)?;
+            self.round(dft, grinder, round, proof, challenger, &mut round_state)?;
         }
 
         Ok(())
     }
 
+    /// Observes the initial commitment and answers its out-of-domain (OOD) queries.
+    ///
+    /// The configuration fingerprint is recorded in the proof; the transcript absorbed it with
+    /// the domain separator.
+    /// The OOD points are sampled from the transcript right after the Merkle root, the
+    /// polynomial is evaluated at each of them, and the answers are both stored in the proof
+    /// and added to `statement` as equality constraints.
+    ///
+    /// This mirrors `CommitmentReader::parse_commitment` on the verifier side.
+    pub(crate) fn observe_commitment(
+        &self,
+        proof: &mut WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        statement: &mut InitialStatement<F, EF>,
+        commitment: MT::Commitment,
+    ) where
+        Challenger: CanObserve<MT::Commitment>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
+    {
+        // Record the configuration, so that the verifier can reject a mismatch upfront
+        proof.config_fingerprint = self.fingerprint();
+
+        // Use CanObserve<Hash<F, W, N>> which both DuplexChallenger and SerializingChallenger implement
+        challenger.observe(commitment.clone());
+        proof.initial_commitment = Some(commitment);
+
+        proof.initial_ood_answers = (0..self.commitment_ood_samples)
+            .map(|_| {
+                let point = Point::expand_from_univariate(
+                    challenger.sample_algebra_element(),
+                    self.num_variables,
+                );
+                let eval = info_span!("ood evaluation").in_scope(|| statement.evaluate(&point));
+                challenger.observe_algebra_element(eval);
+                eval
+            })
+            .collect();
+    }
+
     #[instrument(skip_all, fields(round_number = round_index, log_size = self.num_variables - self.folding_factor.total_number(round_index)))]
     #[allow(clippy::too_many_lines)]
     #[allow(clippy::type_complexity)]
     fn round<Dft: TwoAdicSubgroupDft<F>>(
         &self,
         dft: &Dft,
+        grinder: &Grinder,
         round_index: usize,
         proof: &mut WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
         round_state: &mut RoundState<
             EF,
             F,
-            MT::ProverData<DenseMatrix<F>>,
-            MT::ProverData<FlatMatrixView<F, EF, DenseMatrix<EF>>>,
+            InitialProverData<'_, MT::ProverData<DenseMatrix<F>>>,
+            RoundProverData<F, EF, MT>,
         >,
-    ) -> Result<(), FiatShamirError>
+    ) -> Result<(), ProverError>
     where
-        Challenger: CanObserve<MT::Commitment>,
+        Challenger: CanObserve<RoundCommitment<F, MT>>,
     {
-        let folded_evaluations = &round_state.sumcheck_prover.evals();
+        let folded_evaluations = round_state.sumcheck_prover.evals();
         let num_variables = self.num_variables - self.folding_factor.total_number(round_index);
         assert_eq!(num_variables, folded_evaluations.num_vars());
 
         // Base case: final round reached
         if round_index == self.n_rounds() {
-            return self.final_round(round_index, proof, challenger, round_state);
+            drop(folded_evaluations);
+            return self.final_round(grinder, round_index, proof, challenger, round_state);
         }
 
         let round_params = &self.round_parameters[round_index];
@@ -161,27 +384,36 @@ where
         let folding_factor_next = self.folding_factor.at_round(round_index + 1);
         let inv_rate = self.inv_rate(round_index);
 
-        // Transpose for reverse variable order
-        // And then pad with zeros
-        let padded = info_span!("transpose & pad").in_scope(|| {
-            let num_vars = folded_evaluations.num_vars();
-            let mut mat = RowMajorMatrixView::new(
-                folded_evaluations.as_slice(),
-                1 << (num_vars - folding_factor_next),
-            )
-            .transpose();
-
-            mat.pad_to_height(inv_rate * (1 << (num_vars - folding_factor_next)), EF::ZERO);
-            mat
+        // Transpose for reverse variable order and pad with zeros.
+        //
+        // The padded matrix is allocated once, at its final size, and the evaluations are
+        // transposed into its top rows; the copy of the evaluations is released right after.
+        let evals_len = folded_evaluations.as_slice().len();
+        let padded_bytes = inv_rate * evals_len * size_of::<EF>();
+        let padded = info_span!("transpose & pad", bytes = padded_bytes).in_scope(|| {
+            let height = 1 << (num_variables - folding_factor_next);
+            let width = evals_len / height;
+            let mut values = EF::zero_vec(inv_rate * evals_len);
+            RowMajorMatrixView::new(folded_evaluations.as_slice(), height).transpose_into(
+                &mut RowMajorMatrixViewMut::new(&mut values[..evals_len], width),
+            );
+            RowMajorMatrix::new(values, width)
         });
+        drop(folded_evaluations);
 
         // Perform DFT on the padded evaluations matrix
-        let folded_matrix = info_span!("dft", height = padded.height(), width = padded.width())
-            .in_scope(|| dft.dft_algebra_batch(padded).to_row_major_matrix());
-
-        let extension_mmcs = ExtensionMmcs::new(self.mmcs.clone());
-        let (root, prover_data) =
-            info_span!("commit matrix").in_scope(|| extension_mmcs.commit_matrix(folded_matrix));
+        let folded_matrix = info_span!(
+            "dft",
+            height = padded.height(),
+            width = padded.width(),
+            bytes = padded_bytes
+        )
+        .in_scope(|| dft.dft_algebra_batch(padded).to_row_major_matrix());
+
+        // The tree keeps the evaluations as leaves until its queries are answered next round.
+        let extension_mmcs = ExtensionMmcs::new(self.mmcs.round_mmcs().clone());
+        let (root, prover_data) = info_span!("commit matrix", leaves_bytes = padded_bytes)
+            .in_scope(|| extension_mmcs.commit_matrix(folded_matrix));
 
         // Observe the round merkle tree commitment
         challenger.observe(root.clone());
@@ -219,7 +451,12 @@ where
         // *before* receiving the queries, we make it computationally infeasible to "shop" for
         // favorable challenges. The grinding effectively "locks in" the prover's commitment.
         if round_params.pow_bits > 0 {
-            proof.rounds[round_index].pow_witness = challenger.grind(round_params.pow_bits);
+            proof.rounds[round_index].pow_witness = grinder
+                .grind(challenger, round_params.pow_bits)
+                .map_err(|source| ProverError::Pow {
+                    round: round_index,
+                    source,
+                })?;
         }
 
         challenger.sample();
@@ -239,67 +476,57 @@ where
 
         let mut stir_statement = SelectStatement::initialize(num_variables);
 
-        // Initialize vector of queries
-        let mut queries = Vec::with_capacity(stir_challenges_indexes.len());
-
-        // Collect Merkle proofs for stir queries
-        match &round_state.merkle_prover_data {
+        // Open the queried leaves and fold each of them at the folding randomness.
+        //
+        // Queries are independent, so they are processed in parallel; `unzip` preserves
+        // their order, keeping the proof deterministic.
+        let folding_randomness = &round_state.folding_randomness;
+        let (queries, stir_evals): (Vec<_>, Vec<_>) = match &round_state.merkle_prover_data {
             None => {
-                let mut answers = Vec::with_capacity(stir_challenges_indexes.len());
-                for challenge in &stir_challenges_indexes {
-                    let commitment = self
-                        .mmcs
-                        .open_batch(*challenge, &round_state.commitment_merkle_prover_data);
-                    let answer = commitment.opened_values[0].clone();
-                    answers.push(answer.clone());
-
-                    queries.push(QueryOpening::Base {
-                        values: answer.clone(),
-                        proof: commitment.opening_proof,
-                    });
-                }
-
-                // Process each set of evaluations retrieved from the Merkle tree openings.
-                for (answer, var) in answers.iter().zip(stir_vars.into_iter()) {
-                    let evals = Poly::new(answer.clone());
-                    // Fold the polynomial represented by the `answer` evaluations using the verifier's challenge.
-                    // The evaluation method depends on whether this is a "skip round" or a "standard round".
-
-                    // Case 2: Standard Sumcheck Round
-                    //
-                    // The `answer` represents a standard multilinear polynomial.
-
-                    // Perform a standard multilinear evaluation at the full challenge point `r`.
-                    let eval = evals.eval_base(&round_state.folding_randomness);
-                    stir_statement.add_constraint(var, eval);
-                }
-            }
-            Some(data) => {
-                let mut answers = Vec::with_capacity(stir_challenges_indexes.len());
-                for challenge in &stir_challenges_indexes {
-                    let commitment = extension_mmcs.open_batch(*challenge, data);
-                    let answer = commitment.opened_values[0].clone();
-                    answers.push(answer.clone());
-                    queries.push(QueryOpening::Extension {
-                        values: answer.clone(),
-                        proof: commitment.opening_proof,
-                    });
-                }
-
-                // Process each set of evaluations retrieved from the Merkle tree openings.
-                for (answer, var) in answers.iter().zip(stir_vars.into_iter()) {
-                    // Wrap the evaluations to represent the polynomial.
-                    let evals = Poly::new(answer.clone());
-                    // Perform a standard multilinear evaluation at the full challenge point `r`.
-                    let eval = evals.eval_ext::<F>(&round_state.folding_randomness);
-                    stir_statement.add_constraint(var, eval);
-                }
+                let (mmcs, prover_data) =
+                    (&self.mmcs, round_state.commitment_merkle_prover_data.get());
+                stir_challenges_indexes
+                    .par_iter()
+                    .map(|&challenge| {
+                        let mut opening = mmcs.open_batch(challenge, prover_data);
+                        let values = opening.opened_values.swap_remove(0);
+                        let eval = Poly::new(values.clone()).eval_base(folding_randomness);
+                        let query = QueryOpening::Base {
+                            values,
+                            proof: opening.opening_proof,
+                        };
+                        (query, eval)
+                    })
+                    .unzip()
             }
+            Some(data) => stir_challenges_indexes
+                .par_iter()
+                .map(|&challenge| {
+                    let mut opening = extension_mmcs.open_batch(challenge, data);
+                    let values = opening.opened_values.swap_remove(0);
+                    let eval = Poly::new(values.clone()).eval_ext::<F>(folding_randomness);
+                    let query = QueryOpening::Extension {
+                        values,
+                        proof: opening.opening_proof,
+                    };
+                    (query, eval)
+                })
+                .unzip(),
+        };
+
+        for (var, eval) in stir_vars.into_iter().zip(stir_evals) {
+            stir_statement.add_constraint(var, eval);
         }
 
         // Store queries in proof
         proof.rounds[round_index].queries = queries;
 
+        // The previous tree has answered all its queries: release it now rather than after
+        // the sumcheck, so that it does not add to the sumcheck peak memory. The initial tree
+        // is only freed if the prover owns it.
+        round_state.merkle_prover_data = Some(prover_data);
+        round_state.commitment_merkle_prover_data.release();
+
         let constraint = Constraint::new(
             challenger.sample_algebra_element(),
             ood_statement,
@@ -307,18 +534,24 @@ where
         );
 
         let mut sumcheck_data: SumcheckData<F, EF> = SumcheckData::default();
-        let folding_randomness = round_state.sumcheck_prover.compute_sumcheck_polynomials(
-            &mut sumcheck_data,
-            challenger,
-            folding_factor_next,
-            round_params.folding_pow_bits,
-            Some(constraint),
-        );
+        let folding_randomness = grinder
+            .run(challenger, |challenger| {
+                round_state.sumcheck_prover.compute_sumcheck_polynomials(
+                    &mut sumcheck_data,
+                    challenger,
+                    folding_factor_next,
+                    round_params.folding_pow_bits,
+                    Some(constraint),
+                )
+            })
+            .map_err(|source| ProverError::SumcheckPow {
+                round: round_index,
+                source,
+            })?;
         proof.set_sumcheck_data_at(sumcheck_data, round_index);
 
         // Update round state
         round_state.folding_randomness = folding_randomness;
-        round_state.merkle_prover_data = Some(prover_data);
 
         Ok(())
     }
@@ -327,22 +560,24 @@ where
     #[allow(clippy::type_complexity)]
     fn final_round(
         &self,
+        grinder: &Grinder,
         round_index: usize,
         proof: &mut WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
         round_state: &mut RoundState<
             EF,
             F,
-            MT::ProverData<DenseMatrix<F>>,
-            MT::ProverData<FlatMatrixView<F, EF, DenseMatrix<EF>>>,
+            InitialProverData<'_, MT::ProverData<DenseMatrix<F>>>,
+            RoundProverData<F, EF, MT>,
         >,
-    ) -> Result<(), FiatShamirError>
+    ) -> Result<(), ProverError>
 where {
         // Directly send coefficients of the polynomial to the verifier.
-        challenger.observe_algebra_slice(round_state.sumcheck_prover.evals().as_slice());
+        let final_poly = round_state.sumcheck_prover.evals();
+        challenger.observe_algebra_slice(final_poly.as_slice());
 
         // Store the final polynomial in the proof
-        proof.final_poly = Some(round_state.sumcheck_prover.evals());
+        proof.final_poly = Some(final_poly);
 
         // CRITICAL: Perform proof-of-work grinding to finalize the transcript before querying.
         //
@@ -358,7 +593,13 @@ where {
         // *before* receiving the queries, we make it computationally infeasible to "shop" for
         // favorable challenges. The grinding effectively "locks in" the prover's commitment.
         if self.final_pow_bits > 0 {
-            proof.final_pow_witness = challenger.grind(self.final_pow_bits);
+            proof.final_pow_witness =
+                grinder
+                    .grind(challenger, self.final_pow_bits)
+                    .map_err(|source| ProverError::Pow {
+                        round: round_index,
+                        source,
+                    })?;
         }
 
         // Final verifier queries and answers. The indices are over the folded domain.
@@ -372,42 +613,53 @@ where {
             challenger,
         )?;
 
-        let extension_mmcs = ExtensionMmcs::new(self.mmcs.clone());
-        match &round_state.merkle_prover_data {
+        // Queries are opened in parallel, `collect` preserves their order.
+        let extension_mmcs = ExtensionMmcs::new(self.mmcs.round_mmcs().clone());
+        proof.final_queries = match &round_state.merkle_prover_data {
             None => {
-                for challenge in final_challenge_indexes {
-                    let commitment = self
-                        .mmcs
-                        .open_batch(challenge, &round_state.commitment_merkle_prover_data);
-
-                    proof.final_queries.push(QueryOpening::Base {
-                        values: commitment.opened_values[0].clone(),
-                        proof: commitment.opening_proof,
-                    });
-                }
+                let (mmcs, prover_data) =
+                    (&self.mmcs, round_state.commitment_merkle_prover_data.get());
+                final_challenge_indexes
+                    .par_iter()
+                    .map(|&challenge| {
+                        let mut opening = mmcs.open_batch(challenge, prover_data);
+                        QueryOpening::Base {
+                            values: opening.opened_values.swap_remove(0),
+                            proof: opening.opening_proof,
+                        }
+                    })
+                    .collect()
             }
 
-            Some(data) => {
-                for challenge in final_challenge_indexes {
-                    let commitment = extension_mmcs.open_batch(challenge, data);
-                    proof.final_queries.push(QueryOpening::Extension {
-                        values: commitment.opened_values[0].clone(),
-                        proof: commitment.opening_proof,
-                    });
-                }
-            }
-        }
+            Some(data) => final_challenge_indexes
+                .par_iter()
+                .map(|&challenge| {
+                    let mut opening = extension_mmcs.open_batch(challenge, data);
+                    QueryOpening::Extension {
+                        values: opening.opened_values.swap_remove(0),
+                        proof: opening.opening_proof,
+                    }
+                })
+                .collect(),
+        };
 
         // Run final sumcheck if required
         if self.final_sumcheck_rounds > 0 {
             let mut sumcheck_data: SumcheckData<F, EF> = SumcheckData::default();
-            round_state.sumcheck_prover.compute_sumcheck_polynomials(
-                &mut sumcheck_data,
-                challenger,
-                self.final_sumcheck_rounds,
-                self.final_folding_pow_bits,
-                None,
-            );
+            grinder
+                .run(challenger, |challenger| {
+                    round_state.sumcheck_prover.compute_sumcheck_polynomials(
+                        &mut sumcheck_data,
+                        challenger,
+                        self.final_sumcheck_rounds,
+                        self.final_folding_pow_bits,
+                        None,
+                    )
+                })
+                .map_err(|source| ProverError::SumcheckPow {
+                    round: round_index,
+                    source,
+                })?;
             proof.set_final_sumcheck_data(sumcheck_data);
         }
 
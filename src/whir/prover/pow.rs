@@ -0,0 +1,364 @@
+//! Proof-of-work grinding.
+//!
+//! [`Grinder`] searches for a proof-of-work witness on every core, in batches of nonces.
+//! Between two batches it checks its stop conditions, so that a search too expensive for the
+//! configured number of bits can be cancelled or bounded in time instead of running forever.
+//!
+//! The prover grinds every proof-of-work of a proof with it: the ones preceding the STIR
+//! queries directly, and the ones of the sumchecks through a [`GrinderChallenger`].
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+use p3_field::Field;
+use p3_maybe_rayon::prelude::*;
+use thiserror::Error;
+use tracing::{field, info_span};
+
+/// Default number of nonces tried between two checks of the stop conditions.
+pub const DEFAULT_BATCH_SIZE: usize = 1 << 16;
+
+/// Reason a proof-of-work search stopped without a witness.
+///
+/// `attempts` counts the nonces of the batches started, a slight overestimate of the nonces
+/// actually tried.
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrindingError {
+    /// The cancellation flag was raised.
+    #[error("proof-of-work search cancelled after {attempts} attempts")]
+    Cancelled { attempts: u64 },
+
+    /// The time budget ran out.
+    #[error("proof-of-work search exceeded its time budget after {attempts} attempts")]
+    TimedOut { attempts: u64 },
+}
+
+/// Parallel proof-of-work search, which can be cancelled or bounded in time.
+///
+/// The default grinder has no stop condition: like `GrindingChallenger::grind`, it returns
+/// only once a witness is found.
+#[derive(Debug, Clone)]
+pub struct Grinder {
+    /// Flag stopping the search once raised.
+    cancel: Option<Arc<AtomicBool>>,
+    /// Maximum duration of a single search.
+    #[cfg(feature = "std")]
+    time_budget: Option<Duration>,
+    /// Number of nonces tried between two checks of the stop conditions.
+    batch_size: usize,
+}
+
+impl Default for Grinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Grinder {
+    /// Creates a grinder without stop conditions.
+    pub const fn new() -> Self {
+        Self {
+            cancel: None,
+            #[cfg(feature = "std")]
+            time_budget: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+
+    /// Stops every search once `flag` is raised, e.g. from another thread.
+    #[must_use]
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    /// Stops a search once it has run for `budget`.
+    ///
+    /// The budget applies to each search, i.e. to each proof-of-work of a proof separately.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub const fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Checks the stop conditions every `batch_size` nonces.
+    ///
+    /// Smaller batches react faster to a stop condition, larger ones synchronize the threads
+    /// less often.
+    ///
+    /// # Panics
+    /// Panics if `batch_size` is zero.
+    #[must_use]
+    pub const fn with_batch_size(mut self, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        self.batch_size = batch_size;
+        self
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    }
+
+    /// Finds a witness of `bits` bits of proof-of-work and absorbs it into `challenger`.
+    ///
+    /// This is a drop-in replacement for `challenger.grind(bits)`: on success, the witness is
+    /// accepted by `check_witness` and the challenger is left in the same state. The number
+    /// of attempts and their rate are recorded on the `grind` tracing span.
+    ///
+    /// # Errors
+    /// Returns a [`GrindingError`] if a stop condition is met first. The challenger is then
+    /// left untouched.
+    pub fn grind<F, Challenger>(
+        &self,
+        challenger: &mut Challenger,
+        bits: usize,
+    ) -> Result<F, GrindingError>
+    where
+        F: Field,
+        Challenger: GrindingChallenger<Witness = F>,
+    {
+        let span = info_span!(
+            "grind",
+            bits,
+            attempts = field::Empty,
+            attempts_per_sec = field::Empty
+        );
+        let _guard = span.enter();
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+
+        let batch_size = self.batch_size as u64;
+        let mut attempts = 0;
+        let witness = loop {
+            if self.is_cancelled() {
+                return Err(GrindingError::Cancelled { attempts });
+            }
+            #[cfg(feature = "std")]
+            if self
+                .time_budget
+                .is_some_and(|budget| start.elapsed() >= budget)
+            {
+                return Err(GrindingError::TimedOut { attempts });
+            }
+
+            let found = (attempts..attempts + batch_size)
+                .into_par_iter()
+                .map(F::from_u64)
+                .find_any(|&witness| challenger.clone().check_witness(bits, witness));
+            attempts += batch_size;
+            if let Some(witness) = found {
+                break witness;
+            }
+        };
+
+        span.record("attempts", attempts);
+        #[cfg(feature = "std")]
+        span.record(
+            "attempts_per_sec",
+            attempts as f64 / start.elapsed().as_secs_f64(),
+        );
+
+        let accepted = challenger.check_witness(bits, witness);
+        debug_assert!(accepted, "the witness found must be accepted");
+        Ok(witness)
+    }
+}
+
+/// Challenger grinding its proofs-of-work with a [`Grinder`].
+///
+/// Sumchecks grind through `GrindingChallenger::grind`, which cannot fail. Run on this
+/// wrapper with [`Grinder::run`], their searches stop with the grinder instead: the stop
+/// reason is kept, the later searches are skipped and the transcript is discarded.
+#[derive(Debug, Clone)]
+pub struct GrinderChallenger<'a, C> {
+    inner: C,
+    grinder: &'a Grinder,
+    stopped: Option<GrindingError>,
+}
+
+impl Grinder {
+    /// Runs `f` on a copy of `challenger` whose proofs-of-work are ground by `self`.
+    ///
+    /// On success, `challenger` is left in the state `f` brought the copy to.
+    ///
+    /// # Errors
+    /// Returns the [`GrindingError`] of the first search that stopped. The challenger is then
+    /// left untouched.
+    pub fn run<C, T>(
+        &self,
+        challenger: &mut C,
+        f: impl FnOnce(&mut GrinderChallenger<'_, C>) -> T,
+    ) -> Result<T, GrindingError>
+    where
+        C: Clone,
+    {
+        let mut wrapped = GrinderChallenger {
+            inner: challenger.clone(),
+            grinder: self,
+            stopped: None,
+        };
+        let result = f(&mut wrapped);
+        if let Some(err) = wrapped.stopped {
+            return Err(err);
+        }
+        *challenger = wrapped.inner;
+        Ok(result)
+    }
+}
+
+impl<C, T> CanObserve<T> for GrinderChallenger<'_, C>
+where
+    C: CanObserve<T>,
+{
+    fn observe(&mut self, value: T) {
+        self.inner.observe(value);
+    }
+}
+
+impl<C, T> CanSample<T> for GrinderChallenger<'_, C>
+where
+    C: CanSample<T>,
+{
+    fn sample(&mut self) -> T {
+        self.inner.sample()
+    }
+}
+
+impl<C, T> CanSampleBits<T> for GrinderChallenger<'_, C>
+where
+    C: CanSampleBits<T>,
+{
+    fn sample_bits(&mut self, bits: usize) -> T {
+        self.inner.sample_bits(bits)
+    }
+}
+
+impl<C, F> FieldChallenger<F> for GrinderChallenger<'_, C>
+where
+    C: FieldChallenger<F>,
+    F: Field,
+{
+}
+
+impl<C> GrindingChallenger for GrinderChallenger<'_, C>
+where
+    C: GrindingChallenger,
+{
+    type Witness = C::Witness;
+
+    /// Grinds with the grinder. Once a search has stopped, returns zero without searching.
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        if self.stopped.is_none() {
+            match self.grinder.grind(&mut self.inner, bits) {
+                Ok(witness) => return witness,
+                Err(err) => self.stopped = Some(err),
+            }
+        }
+        Self::Witness::ZERO
+    }
+
+    fn check_witness(&mut self, bits: usize, witness: Self::Witness) -> bool {
+        self.inner.check_witness(bits, witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::{CanSample, DuplexChallenger};
+    use rand::{SeedableRng, rngs::SmallRng};
+
+    use super::*;
+
+    type F = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+
+    fn challenger() -> MyChallenger {
+        MyChallenger::new(Perm::new_from_rng_128(&mut SmallRng::seed_from_u64(1)))
+    }
+
+    #[test]
+    fn test_grind_matches_check_witness() {
+        for bits in [1, 4, 8, 12] {
+            let mut prover = challenger();
+            let witness = Grinder::new()
+                .with_batch_size(1 << 6)
+                .grind(&mut prover, bits)
+                .unwrap();
+
+            // The verifier accepts the witness and ends up in the prover's state.
+            let mut verifier = challenger();
+            assert!(verifier.check_witness(bits, witness));
+            let (p, v): (F, F) = (prover.sample(), verifier.sample());
+            assert_eq!(p, v);
+        }
+    }
+
+    #[test]
+    fn test_grind_cancelled() {
+        let flag = Arc::new(AtomicBool::new(true));
+        let mut challenger = challenger();
+        let before: F = challenger.clone().sample();
+
+        let result = Grinder::new()
+            .with_cancel_flag(flag)
+            .grind(&mut challenger, 8);
+
+        assert_eq!(result, Err(GrindingError::Cancelled { attempts: 0 }));
+        assert_eq!(challenger.sample(), before);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_grind_time_budget() {
+        let mut challenger = challenger();
+
+        // Unreachable difficulty: the search can only stop on its budget.
+        let result = Grinder::new()
+            .with_time_budget(Duration::from_millis(10))
+            .with_batch_size(1 << 8)
+            .grind(&mut challenger, 60);
+
+        assert!(matches!(result, Err(GrindingError::TimedOut { attempts }) if attempts > 0));
+    }
+
+    #[test]
+    fn test_run_grinds_with_the_grinder() {
+        let bits = 8;
+        let mut prover = challenger();
+        let witness = Grinder::new()
+            .run(&mut prover, |challenger| challenger.grind(bits))
+            .unwrap();
+
+        let mut verifier = challenger();
+        assert!(verifier.check_witness(bits, witness));
+        let (p, v): (F, F) = (prover.sample(), verifier.sample());
+        assert_eq!(p, v);
+    }
+
+    #[test]
+    fn test_run_cancelled() {
+        let flag = Arc::new(AtomicBool::new(true));
+        let mut challenger = challenger();
+        let before: F = challenger.clone().sample();
+
+        // Later searches are skipped, and the whole run reports the first stop.
+        let result = Grinder::new()
+            .with_cancel_flag(flag)
+            .run(&mut challenger, |challenger| {
+                let witnesses = [challenger.grind(8), challenger.grind(8)];
+                let _: F = challenger.sample();
+                witnesses
+            });
+
+        assert_eq!(result, Err(GrindingError::Cancelled { attempts: 0 }));
+        assert_eq!(challenger.sample(), before);
+    }
+}
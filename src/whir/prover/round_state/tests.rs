@@ -12,12 +12,12 @@ use rand::{SeedableRng, rngs::SmallRng};
 
 use crate::{
     constraints::statement::initial::InitialStatement,
-    fiat_shamir::domain_separator::DomainSeparator,
     parameters::{
         FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
     },
     whir::{
         committer::{ProverData, ProverDataExt, writer::CommitmentWriter},
+        config::domain_separator,
         proof::WhirProof,
         prover::round_state::RoundState,
     },
@@ -108,7 +108,7 @@ fn setup_domain_and_commitment(
     let whir_proof = WhirProof::from_protocol_parameters(&protocol_params, poly.num_vars());
 
     // Create a new Fiat-Shamir domain separator.
-    let mut domsep = DomainSeparator::new(vec![]);
+    let mut domsep = domain_separator(params);
 
     // Observe the public statement into the transcript for binding.
     domsep.commit_statement::<_, _, DIGEST_ELEMS>(params);
@@ -121,21 +121,10 @@ fn setup_domain_and_commitment(
     let mut prover_challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
     domsep.observe_domain_separator(&mut prover_challenger);
 
-    // Create a committer using the protocol configuration (Merkle parameters, hashers, etc.).
-    let committer = CommitmentWriter::new(params);
-    let mut initial_statement = InitialStatement::new(poly, 0, SumcheckStrategy::default());
-    let mut proof =
-        WhirProof::from_protocol_parameters(&protocol_params, initial_statement.num_variables());
-
     // Perform DFT-based commitment to the polynomial, producing a prover data
     // which includes the Merkle tree and polynomial values.
-    let prover_data = committer
-        .commit(
-            &Radix2DFTSmallBatch::<F>::default(),
-            &mut proof,
-            &mut prover_challenger,
-            &mut initial_statement,
-        )
+    let (_, prover_data) = CommitmentWriter::new(params)
+        .commit(&Radix2DFTSmallBatch::<F>::default(), &poly)
         .unwrap();
 
     // Return all initialized components needed for round state setup.
@@ -0,0 +1,146 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::extension::BinomialExtensionField;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+
+use crate::{
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        config::domain_separator,
+        proof::WhirProof,
+        prover::{Prover, pow::Grinder},
+    },
+};
+
+type F = BabyBear;
+type EF = BinomialExtensionField<F, 4>;
+
+type Perm = Poseidon2BabyBear<16>;
+
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+type MyMmcs = MerkleTreeMmcs<F, F, MyHash, MyCompress, 2, 8>;
+
+#[test]
+fn test_initial_ood_answers_are_evaluations() {
+    let num_variables = 6;
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let whir_params = ProtocolParameters {
+        security_level: 32,
+        pow_bits: 0,
+        rs_domain_initial_reduction_factor: 1,
+        folding_factor: FoldingFactor::Constant(2),
+        mmcs: MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()), 0),
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+    };
+    let mut params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params);
+    params.commitment_ood_samples = 3;
+
+    let polynomial = Poly::<F>::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let dft = Radix2DFTSmallBatch::<F>::default();
+    let (root, _) = CommitmentWriter::new(&params)
+        .commit(&dft, &polynomial)
+        .unwrap();
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    let mut challenger = MyChallenger::new(perm);
+    domainsep.observe_domain_separator(&mut challenger);
+
+    let mut proof = WhirProof::<F, EF, MyMmcs>::default();
+    let mut statement = params.initial_statement(polynomial.clone(), SumcheckStrategy::Classic);
+    Prover(&params).observe_commitment(&mut proof, &mut challenger.clone(), &mut statement, root);
+    assert_eq!(proof.initial_ood_answers.len(), 3);
+
+    // Replay the transcript: every answer is the polynomial evaluated at the point sampled
+    // right before it.
+    let mut replay = challenger.clone();
+    replay.observe(root.clone());
+    for &answer in &proof.initial_ood_answers {
+        let point = Point::expand_from_univariate(replay.sample_algebra_element(), num_variables);
+        assert_eq!(polynomial.eval_base(&point), answer);
+        replay.observe_algebra_element(answer);
+    }
+
+    // The verifier samples the same points and reads the same answers.
+    let parsed = CommitmentReader::new(&params)
+        .parse_commitment::<F, 8>(&proof, &mut challenger)
+        .unwrap();
+    assert_eq!(parsed.root, root);
+    assert_eq!(parsed.ood_statement, statement.normalize());
+}
+
+#[test]
+fn test_prove_owned_matches_prove() {
+    let num_variables = 8;
+    let mut rng = SmallRng::seed_from_u64(1);
+    let perm = Perm::new_from_rng_128(&mut rng);
+    let whir_params = ProtocolParameters {
+        security_level: 32,
+        pow_bits: 0,
+        rs_domain_initial_reduction_factor: 1,
+        folding_factor: FoldingFactor::Constant(2),
+        mmcs: MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm.clone()), 0),
+        soundness_type: SecurityAssumption::CapacityBound,
+        starting_log_inv_rate: 1,
+    };
+    let params = WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+    assert!(params.n_rounds() > 0);
+
+    let polynomial = Poly::<F>::new((0..1 << num_variables).map(|_| rng.random()).collect());
+    let mut statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+    let _ = statement.evaluate(&Point::rand(&mut rng, num_variables));
+
+    let mut domainsep = domain_separator(&params);
+    domainsep.commit_statement::<_, _, 8>(&params);
+    domainsep.add_whir_proof::<_, _, 8>(&params);
+    let mut challenger = MyChallenger::new(perm);
+    domainsep.observe_domain_separator(&mut challenger);
+
+    let dft = Radix2DFTSmallBatch::<F>::default();
+    let (root, prover_data) = CommitmentWriter::new(&params)
+        .commit(&dft, &statement.poly)
+        .unwrap();
+
+    // Releasing the initial tree after the first round changes nothing in the proof.
+    let mut borrowed =
+        WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+    Prover(&params)
+        .prove(
+            &dft,
+            &mut borrowed,
+            &mut challenger.clone(),
+            &mut statement.clone(),
+            root.clone(),
+            &prover_data,
+        )
+        .unwrap();
+    let mut owned =
+        WhirProof::<F, EF, MyMmcs>::from_protocol_parameters(&whir_params, num_variables);
+    Prover(&params)
+        .prove_owned(
+            &Grinder::default(),
+            &dft,
+            &mut owned,
+            &mut challenger,
+            &mut statement,
+            root,
+            prover_data,
+        )
+        .unwrap();
+
+    assert_eq!(
+        bincode::serialize(&owned).unwrap(),
+        bincode::serialize(&borrowed).unwrap()
+    );
+}
@@ -0,0 +1,281 @@
+//! Transcript recording to locate where a verifier diverges from the prover.
+//!
+//! Wrapping the challenger of both parties in a [`TranscriptRecorder`] logs every
+//! Fiat-Shamir operation. Comparing the prover trace with the one of a failing verifier
+//! with [`first_divergence`] pinpoints the first operation where they disagree, which is
+//! usually the first piece of proof data that was tampered with or mis-serialized.
+
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Debug;
+
+use p3_challenger::{CanObserve, CanSample, CanSampleBits, FieldChallenger, GrindingChallenger};
+use p3_field::{ExtensionField, Field, TwoAdicField};
+use p3_multilinear_util::point::Point;
+use serde::Serialize;
+
+use super::{Verifier, errors::VerifierError};
+use crate::{
+    constraints::statement::EqStatement,
+    whir::{
+        committer::reader::ParsedCommitment,
+        mmcs::{RoundCommitment, RoundMmcs, RoundProof, WhirMmcs},
+        proof::WhirProof,
+    },
+};
+
+/// Kind of Fiat-Shamir operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// A value absorbed into the transcript.
+    Observe,
+    /// A value squeezed from the transcript.
+    Sample,
+    /// Random bits squeezed from the transcript.
+    SampleBits { bits: usize },
+    /// A proof-of-work witness, found by the prover or checked by the verifier.
+    Pow { bits: usize },
+}
+
+/// A single recorded Fiat-Shamir operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptEvent {
+    /// Kind of the operation.
+    pub op: TranscriptOp,
+    /// Debug representation of the observed, sampled or witness value.
+    pub value: String,
+}
+
+/// Challenger wrapper logging every operation performed on the inner challenger.
+///
+/// Extension field elements are absorbed and sampled through the default methods of
+/// [`FieldChallenger`], so they are logged coefficient by coefficient and the traces of
+/// the prover and the verifier line up operation by operation.
+#[derive(Debug, Clone)]
+pub struct TranscriptRecorder<C> {
+    inner: C,
+    events: Vec<TranscriptEvent>,
+}
+
+impl<C> TranscriptRecorder<C> {
+    /// Wraps `inner`, starting with an empty trace.
+    pub const fn new(inner: C) -> Self {
+        Self {
+            inner,
+            events: Vec::new(),
+        }
+    }
+
+    /// Operations recorded so far.
+    pub fn events(&self) -> &[TranscriptEvent] {
+        &self.events
+    }
+
+    /// Consumes the recorder, returning the recorded operations.
+    pub fn into_events(self) -> Vec<TranscriptEvent> {
+        self.events
+    }
+
+    fn record(&mut self, op: TranscriptOp, value: &impl Debug) {
+        self.events.push(TranscriptEvent {
+            op,
+            value: format!("{value:?}"),
+        });
+    }
+}
+
+impl<C, T> CanObserve<T> for TranscriptRecorder<C>
+where
+    C: CanObserve<T>,
+    T: Debug,
+{
+    fn observe(&mut self, value: T) {
+        self.record(TranscriptOp::Observe, &value);
+        self.inner.observe(value);
+    }
+}
+
+impl<C, T> CanSample<T> for TranscriptRecorder<C>
+where
+    C: CanSample<T>,
+    T: Debug,
+{
+    fn sample(&mut self) -> T {
+        let value = self.inner.sample();
+        self.record(TranscriptOp::Sample, &value);
+        value
+    }
+}
+
+impl<C, T> CanSampleBits<T> for TranscriptRecorder<C>
+where
+    C: CanSampleBits<T>,
+    T: Debug,
+{
+    fn sample_bits(&mut self, bits: usize) -> T {
+        let value = self.inner.sample_bits(bits);
+        self.record(TranscriptOp::SampleBits { bits }, &value);
+        value
+    }
+}
+
+impl<C, F> FieldChallenger<F> for TranscriptRecorder<C>
+where
+    C: FieldChallenger<F>,
+    F: Field,
+{
+}
+
+impl<C> GrindingChallenger for TranscriptRecorder<C>
+where
+    C: GrindingChallenger,
+{
+    type Witness = C::Witness;
+
+    fn grind(&mut self, bits: usize) -> Self::Witness {
+        let witness = self.inner.grind(bits);
+        self.record(TranscriptOp::Pow { bits }, &witness);
+        witness
+    }
+
+    fn check_witness(&mut self, bits: usize, witness: Self::Witness) -> bool {
+        self.record(TranscriptOp::Pow { bits }, &witness);
+        self.inner.check_witness(bits, witness)
+    }
+}
+
+/// First operation where two transcripts disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptDivergence {
+    /// Position of the operation in both traces.
+    pub index: usize,
+    /// Operation of the prover, `None` if its trace ended before.
+    pub prover: Option<TranscriptEvent>,
+    /// Operation of the verifier, `None` if its trace ended before.
+    pub verifier: Option<TranscriptEvent>,
+}
+
+/// Finds the first operation where the prover and verifier traces disagree.
+///
+/// Returns `None` if both traces are identical.
+pub fn first_divergence(
+    prover: &[TranscriptEvent],
+    verifier: &[TranscriptEvent],
+) -> Option<TranscriptDivergence> {
+    let index = prover
+        .iter()
+        .zip(verifier)
+        .position(|(p, v)| p != v)
+        .or_else(|| (prover.len() != verifier.len()).then(|| prover.len().min(verifier.len())))?;
+
+    Some(TranscriptDivergence {
+        index,
+        prover: prover.get(index).cloned(),
+        verifier: verifier.get(index).cloned(),
+    })
+}
+
+/// Failed verification together with the location where the transcripts diverged.
+#[derive(Debug)]
+pub struct VerifierDiagnosis {
+    /// Error returned by the verifier.
+    pub error: VerifierError,
+    /// First diverging transcript operation, `None` if the transcripts agree.
+    pub divergence: Option<TranscriptDivergence>,
+}
+
+impl<EF, F, MT, C> Verifier<'_, EF, F, MT, TranscriptRecorder<C>>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    C: FieldChallenger<F>
+        + GrindingChallenger<Witness = F>
+        + CanObserve<MT::Commitment>
+        + CanObserve<RoundCommitment<F, MT>>,
+    MT: WhirMmcs<F> + Sync,
+    MT::Commitment: Debug + Serialize + Sync,
+    MT::Proof: Sync,
+    RoundMmcs<F, MT>: Sync,
+    RoundCommitment<F, MT>: Debug + Serialize + Sync,
+    RoundProof<F, MT>: Sync,
+{
+    /// Verifies a proof and, on failure, compares the verifier transcript with the prover's.
+    ///
+    /// `challenger` must have recorded the verifier side up to this point (domain separator
+    /// and parsed commitment), exactly as `prover_trace` was recorded on the prover side.
+    ///
+    /// # Errors
+    /// Returns the verifier error along with the first diverging transcript operation.
+    pub fn debug_verify(
+        &self,
+        proof: &WhirProof<F, EF, MT>,
+        challenger: &mut TranscriptRecorder<C>,
+        parsed_commitment: &ParsedCommitment<EF, MT::Commitment>,
+        statement: EqStatement<EF>,
+        prover_trace: &[TranscriptEvent],
+    ) -> Result<Point<EF>, VerifierDiagnosis> {
+        self.verify(proof, challenger, parsed_commitment, statement)
+            .map_err(|error| VerifierDiagnosis {
+                error,
+                divergence: first_divergence(prover_trace, challenger.events()),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    fn event(op: TranscriptOp, value: u32) -> TranscriptEvent {
+        TranscriptEvent {
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_first_divergence_identical() {
+        let trace = vec![
+            event(TranscriptOp::Observe, 1),
+            event(TranscriptOp::Sample, 2),
+        ];
+        assert_eq!(first_divergence(&trace, &trace), None);
+    }
+
+    #[test]
+    fn test_first_divergence_value() {
+        let prover = vec![
+            event(TranscriptOp::Observe, 1),
+            event(TranscriptOp::Observe, 2),
+            event(TranscriptOp::Sample, 3),
+        ];
+        let verifier = vec![
+            event(TranscriptOp::Observe, 1),
+            event(TranscriptOp::Observe, 5),
+            event(TranscriptOp::Sample, 7),
+        ];
+
+        let divergence = first_divergence(&prover, &verifier).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.prover, Some(event(TranscriptOp::Observe, 2)));
+        assert_eq!(divergence.verifier, Some(event(TranscriptOp::Observe, 5)));
+    }
+
+    #[test]
+    fn test_first_divergence_truncated() {
+        let prover = vec![
+            event(TranscriptOp::Observe, 1),
+            event(TranscriptOp::Pow { bits: 4 }, 2),
+        ];
+        let verifier = vec![event(TranscriptOp::Observe, 1)];
+
+        let divergence = first_divergence(&prover, &verifier).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(
+            divergence.prover,
+            Some(event(TranscriptOp::Pow { bits: 4 }, 2))
+        );
+        assert_eq!(divergence.verifier, None);
+    }
+}
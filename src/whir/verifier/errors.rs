@@ -1,46 +1,150 @@
 //! Verifier error types for WHIR protocol validation.
 
 use alloc::string::String;
+use core::fmt;
 
 use thiserror::Error;
 
 use crate::{fiat_shamir::errors::FiatShamirError, sumcheck::SumcheckError};
 
+/// Phase of the WHIR verifier in which a check is performed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifierPhase {
+    /// Sumcheck rounds folding the initial polynomial.
+    InitialSumcheck,
+    /// Out-of-domain samples of a round commitment.
+    Ood,
+    /// Proof-of-work preceding the STIR queries.
+    Pow,
+    /// STIR queries opened against the previous commitment.
+    Stir,
+    /// Sumcheck rounds folding the polynomial of a round.
+    RoundSumcheck,
+    /// Final polynomial sent in the clear.
+    FinalPoly,
+    /// Sumcheck rounds run on the final polynomial.
+    FinalSumcheck,
+}
+
+impl fmt::Display for VerifierPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::InitialSumcheck => "initial sumcheck",
+            Self::Ood => "OOD",
+            Self::Pow => "PoW",
+            Self::Stir => "STIR",
+            Self::RoundSumcheck => "round sumcheck",
+            Self::FinalPoly => "final polynomial",
+            Self::FinalSumcheck => "final sumcheck",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Errors for WHIR protocol verification.
+///
+/// Errors raised while checking the protocol carry the WHIR round in which they occurred
+/// (the initial sumcheck belongs to round 0, the final phases to round `n_rounds`) and the
+/// verifier phase, available through [`Self::round`] and [`Self::phase`].
 #[derive(Error, Debug)]
 pub enum VerifierError {
     /// Merkle tree proof verification failed for polynomial commitment.
-    #[error("Merkle proof verification failed at position {position}: {reason}")]
-    MerkleProofInvalid { position: usize, reason: String },
+    #[error(
+        "Merkle proof verification failed in round {round} for query {query} at position {position}: {reason}"
+    )]
+    MerkleProofInvalid {
+        round: usize,
+        query: usize,
+        position: usize,
+        reason: String,
+    },
 
     /// Sumcheck verification failed due to inconsistent polynomial evaluations.
-    #[error("Sumcheck verification failed at round {round}: expected {expected}, got {actual}")]
+    #[error("{phase} verification failed in round {round}: expected {expected}, got {actual}")]
     SumcheckFailed {
         round: usize,
+        phase: VerifierPhase,
         expected: String,
         actual: String,
     },
 
-    /// STIR challenge responses are inconsistent or invalid.
-    #[error("STIR challenge {challenge_id} verification failed: {details}")]
+    /// STIR challenge responses are inconsistent with the final polynomial.
+    #[error("STIR challenge {challenge_id} verification failed in round {round}: {details}")]
     StirChallengeFailed {
+        round: usize,
         challenge_id: usize,
         details: String,
     },
 
     /// Sumcheck verification error.
-    #[error(transparent)]
-    Sumcheck(#[from] SumcheckError),
+    #[error("{phase} failed in round {round}: {source}")]
+    Sumcheck {
+        round: usize,
+        phase: VerifierPhase,
+        #[source]
+        source: SumcheckError,
+    },
 
     /// Fiat-Shamir transcript error during verification.
-    #[error(transparent)]
-    FiatShamir(#[from] FiatShamirError),
+    #[error("Fiat-Shamir error in {phase} of round {round}: {source}")]
+    FiatShamir {
+        round: usize,
+        phase: VerifierPhase,
+        #[source]
+        source: FiatShamirError,
+    },
 
-    /// Invalid round index when accessing proof data.
-    #[error("Invalid round index: {index}")]
-    InvalidRoundIndex { index: usize },
+    /// The proof lacks data expected by the configuration.
+    #[error("Malformed proof in {phase} of round {round}: {details}")]
+    MalformedProof {
+        round: usize,
+        phase: VerifierPhase,
+        details: String,
+    },
 
     /// Proof-of-work witness verification failed.
-    #[error("Invalid proof-of-work witness")]
-    InvalidPowWitness,
+    #[error("Invalid proof-of-work witness in round {round}")]
+    InvalidPowWitness { round: usize },
+
+    /// The proof does not embed the evaluation claims it proves.
+    #[error("Proof does not embed its evaluation claims")]
+    MissingClaims,
+
+    /// The evaluation claims embedded in the proof are malformed or differ from the expected ones.
+    #[error("Evaluation claims mismatch: {details}")]
+    ClaimsMismatch { details: String },
+
+    /// The proof was generated with a different WHIR configuration.
+    #[error("Proof was generated for a different WHIR configuration")]
+    ConfigMismatch,
+}
+
+impl VerifierError {
+    /// WHIR round in which the error occurred, if it arose while checking the protocol.
+    pub const fn round(&self) -> Option<usize> {
+        match self {
+            Self::MerkleProofInvalid { round, .. }
+            | Self::SumcheckFailed { round, .. }
+            | Self::StirChallengeFailed { round, .. }
+            | Self::Sumcheck { round, .. }
+            | Self::FiatShamir { round, .. }
+            | Self::MalformedProof { round, .. }
+            | Self::InvalidPowWitness { round } => Some(*round),
+            Self::MissingClaims | Self::ClaimsMismatch { .. } | Self::ConfigMismatch => None,
+        }
+    }
+
+    /// Verifier phase in which the error occurred, if it arose while checking the protocol.
+    pub const fn phase(&self) -> Option<VerifierPhase> {
+        match self {
+            Self::MerkleProofInvalid { .. } => Some(VerifierPhase::Stir),
+            Self::StirChallengeFailed { .. } => Some(VerifierPhase::FinalPoly),
+            Self::SumcheckFailed { phase, .. }
+            | Self::Sumcheck { phase, .. }
+            | Self::FiatShamir { phase, .. }
+            | Self::MalformedProof { phase, .. } => Some(*phase),
+            Self::InvalidPowWitness { .. } => Some(VerifierPhase::Pow),
+            Self::MissingClaims | Self::ClaimsMismatch { .. } | Self::ConfigMismatch => None,
+        }
+    }
 }
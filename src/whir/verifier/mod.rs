@@ -1,12 +1,14 @@
 use alloc::{format, vec, vec::Vec};
-use core::{fmt::Debug, ops::Deref, slice::from_ref};
+use core::{ops::Deref, slice::from_ref};
 
-use errors::VerifierError;
+use errors::{VerifierError, VerifierPhase};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::{BatchOpeningRef, ExtensionMmcs, Mmcs};
 use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Dimensions;
+use p3_maybe_rayon::prelude::*;
 use p3_multilinear_util::{point::Point, poly::Poly};
+use serde::Serialize;
 use tracing::instrument;
 
 use super::{committer::reader::ParsedCommitment, utils::get_challenge_stir_queries};
@@ -19,10 +21,47 @@ use crate::{
     },
     parameters::{RoundConfig, WhirConfig},
     sumcheck::verify_final_sumcheck_rounds,
-    whir::proof::{QueryOpening, WhirProof},
+    whir::{
+        config::WhirConfigExt,
+        mmcs::{RoundCommitment, RoundMmcs, RoundProof, WhirMmcs},
+        proof::{InitialClaims, QueryOpening, WhirProof},
+    },
 };
 
+pub mod diagnostics;
 pub mod errors;
+#[cfg(test)]
+mod tests;
+
+/// Root of the tree opened by a STIR query phase.
+#[derive(Debug)]
+pub enum StirRoot<'a, C, RC> {
+    /// The initial commitment, opened on base field leaves.
+    Initial(&'a C),
+    /// A round commitment, opened on extension field leaves.
+    Round(&'a RC),
+}
+
+impl<'a, C, RC> StirRoot<'a, C, RC> {
+    /// Root of the commitment preceding a round: the latest round commitment, if any, and the
+    /// initial commitment otherwise.
+    pub fn previous<EF>(
+        initial: &'a ParsedCommitment<EF, C>,
+        round: Option<&'a ParsedCommitment<EF, RC>>,
+    ) -> Self {
+        round.map_or(Self::Initial(&initial.root), |round| {
+            Self::Round(&round.root)
+        })
+    }
+}
+
+impl<C, RC> Clone for StirRoot<'_, C, RC> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C, RC> Copy for StirRoot<'_, C, RC> {}
 
 /// Wrapper around the WHIR verifier configuration.
 ///
@@ -42,12 +81,41 @@ where
     F: TwoAdicField,
     EF: ExtensionField<F> + TwoAdicField,
     Challenger: FieldChallenger<F> + GrindingChallenger<Witness = F>,
-    MT: Mmcs<F>,
+    MT: WhirMmcs<F> + Sync,
+    MT::Commitment: Sync,
+    MT::Proof: Sync,
+    RoundMmcs<F, MT>: Sync,
+    RoundCommitment<F, MT>: Sync,
+    RoundProof<F, MT>: Sync,
 {
     pub const fn new(params: &'a WhirConfig<EF, F, MT, Challenger>) -> Self {
         Self(params)
     }
 
+    /// Verifies a self-describing proof against the evaluation claims it embeds.
+    ///
+    /// This is [`Self::verify`] with the statement taken from `proof.claims`.
+    ///
+    /// # Errors
+    /// Returns `VerifierError::MissingClaims` if the proof carries no claims,
+    /// `VerifierError::ClaimsMismatch` if they are malformed or not over
+    /// `num_variables` variables, and any error of [`Self::verify`].
+    pub fn verify_standalone(
+        &self,
+        proof: &WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        parsed_commitment: &ParsedCommitment<EF, MT::Commitment>,
+    ) -> Result<Point<EF>, VerifierError>
+    where
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
+    {
+        let claims = proof.claims.as_ref().ok_or(VerifierError::MissingClaims)?;
+        self.check_claims(claims)?;
+        self.verify(proof, challenger, parsed_commitment, claims.to_statement())
+    }
+
     #[instrument(skip_all)]
     #[allow(clippy::too_many_lines)]
     pub fn verify(
@@ -58,16 +126,34 @@ where
         mut statement: EqStatement<EF>,
     ) -> Result<Point<EF>, VerifierError>
     where
-        Challenger: CanObserve<MT::Commitment>,
+        Challenger: CanObserve<MT::Commitment> + CanObserve<RoundCommitment<F, MT>>,
+        MT::Commitment: Serialize,
+        RoundCommitment<F, MT>: Serialize,
     {
+        // The proof must have been generated with this exact configuration.
+        if proof.config_fingerprint != self.fingerprint() {
+            return Err(VerifierError::ConfigMismatch);
+        }
+
+        // A self-describing proof must describe the statement being verified.
+        if let Some(claims) = &proof.claims {
+            self.check_claims(claims)?;
+            if *claims != InitialClaims::new(self.num_variables, &statement) {
+                return Err(VerifierError::ClaimsMismatch {
+                    details: "embedded claims differ from the verified statement".to_string(),
+                });
+            }
+        }
+
         // During the rounds we collect constraints, combination randomness, folding randomness
         // and we update the claimed sum of constraint evaluation.
         let mut constraints = Vec::new();
         let mut round_folding_randomness = Vec::new();
         let mut claimed_eval = EF::ZERO;
-        let mut prev_commitment = parsed_commitment.clone();
+        // The first round queries the initial commitment, every later one the previous round's.
+        let mut prev_round_commitment: Option<ParsedCommitment<EF, RoundCommitment<F, MT>>> = None;
 
-        statement.concatenate(&prev_commitment.ood_statement);
+        statement.concatenate(&parsed_commitment.ood_statement);
 
         let constraint = Constraint::new(
             challenger.sample_algebra_element(),
@@ -78,11 +164,18 @@ where
         constraint.combine_evals(&mut claimed_eval);
         constraints.push(constraint);
 
-        let folding_randomness = proof.initial_sumcheck.verify_rounds(
-            challenger,
-            &mut claimed_eval,
-            self.starting_folding_pow_bits,
-        )?;
+        let folding_randomness = proof
+            .initial_sumcheck
+            .verify_rounds(
+                challenger,
+                &mut claimed_eval,
+                self.starting_folding_pow_bits,
+            )
+            .map_err(|source| VerifierError::Sumcheck {
+                round: 0,
+                phase: VerifierPhase::InitialSumcheck,
+                source,
+            })?;
 
         round_folding_randomness.push(folding_randomness);
 
@@ -91,20 +184,20 @@ where
             let round_params = &self.round_parameters[round_index];
 
             // Receive commitment to the folded polynomial (likely encoded at higher expansion)
-            let new_commitment = ParsedCommitment::<_, MT::Commitment>::parse_with_round(
+            let new_commitment = ParsedCommitment::<F, RoundCommitment<F, MT>>::parse_round(
                 proof,
                 challenger,
                 round_params.num_variables,
                 round_params.ood_samples,
-                Some(round_index),
-            );
+                round_index,
+            )?;
 
             // Verify in-domain challenges on the previous commitment.
             let stir_statement = self.verify_stir_challenges(
                 proof,
                 challenger,
                 round_params,
-                &prev_commitment,
+                StirRoot::previous(parsed_commitment, prev_round_commitment.as_ref()),
                 round_folding_randomness.last().unwrap(),
                 round_index,
             )?;
@@ -117,44 +210,70 @@ where
             constraint.combine_evals(&mut claimed_eval);
             constraints.push(constraint);
 
-            let folding_randomness = proof.rounds[round_index].sumcheck.verify_rounds(
-                challenger,
-                &mut claimed_eval,
-                round_params.folding_pow_bits,
-            )?;
+            let folding_randomness = proof.rounds[round_index]
+                .sumcheck
+                .verify_rounds(challenger, &mut claimed_eval, round_params.folding_pow_bits)
+                .map_err(|source| VerifierError::Sumcheck {
+                    round: round_index,
+                    phase: VerifierPhase::RoundSumcheck,
+                    source,
+                })?;
 
             round_folding_randomness.push(folding_randomness);
 
             // Update round parameters
-            prev_commitment = new_commitment;
+            prev_round_commitment = Some(new_commitment);
         }
 
         // In the final round we receive the full polynomial instead of a commitment.
-        let Some(final_evaluations) = proof.final_poly.clone() else {
-            panic!("Expected final polynomial");
-        };
+        let final_round_config = self.final_round_config();
+        let final_evaluations =
+            proof
+                .final_poly
+                .clone()
+                .ok_or_else(|| VerifierError::MalformedProof {
+                    round: self.n_rounds(),
+                    phase: VerifierPhase::FinalPoly,
+                    details: "missing final polynomial".to_string(),
+                })?;
+        let expected_len = 1 << final_round_config.num_variables;
+        if final_evaluations.as_slice().len() != expected_len {
+            return Err(VerifierError::MalformedProof {
+                round: self.n_rounds(),
+                phase: VerifierPhase::FinalPoly,
+                details: format!(
+                    "expected {expected_len} final evaluations, got {}",
+                    final_evaluations.as_slice().len()
+                ),
+            });
+        }
 
         // Observe the final polynomial to the challenger
         challenger.observe_algebra_slice(final_evaluations.as_slice());
 
         // Verify in-domain challenges on the previous commitment.
-        let stir_statement = self.verify_stir_challenges(
+        let (stir_points, stir_folds) = self.stir_answers(
             proof,
             challenger,
-            &self.final_round_config(),
-            &prev_commitment,
+            &final_round_config,
+            StirRoot::previous(parsed_commitment, prev_round_commitment.as_ref()),
             round_folding_randomness.last().unwrap(),
             self.n_rounds(),
         )?;
 
-        // Verify stir constraints directly on final polynomial
-        stir_statement
-            .verify(&final_evaluations)
-            .then_some(())
-            .ok_or_else(|| VerifierError::StirChallengeFailed {
-                challenge_id: 0,
-                details: "STIR constraint verification failed on final polynomial".to_string(),
-            })?;
+        // Verify stir constraints directly on final polynomial, one query at a time
+        // so that the first inconsistent one can be reported.
+        for (challenge_id, (point, fold)) in stir_points.into_iter().zip(stir_folds).enumerate() {
+            let stir_statement =
+                SelectStatement::new(final_round_config.num_variables, vec![point], vec![fold]);
+            if !stir_statement.verify(&final_evaluations) {
+                return Err(VerifierError::StirChallengeFailed {
+                    round: self.n_rounds(),
+                    challenge_id,
+                    details: "STIR constraint verification failed on final polynomial".to_string(),
+                });
+            }
+        }
 
         let final_sumcheck_randomness = verify_final_sumcheck_rounds(
             proof.final_sumcheck.as_ref(),
@@ -162,7 +281,12 @@ where
             &mut claimed_eval,
             self.final_sumcheck_rounds,
             self.final_folding_pow_bits,
-        )?;
+        )
+        .map_err(|source| VerifierError::Sumcheck {
+            round: self.n_rounds(),
+            phase: VerifierPhase::FinalSumcheck,
+            source,
+        })?;
 
         round_folding_randomness.push(final_sumcheck_randomness.clone());
 
@@ -183,7 +307,8 @@ where
         let final_value = final_evaluations.eval_ext::<F>(&final_sumcheck_randomness);
         if claimed_eval != evaluation_of_weights * final_value {
             return Err(VerifierError::SumcheckFailed {
-                round: self.final_sumcheck_rounds,
+                round: self.n_rounds(),
+                phase: VerifierPhase::FinalSumcheck,
                 expected: (evaluation_of_weights * final_value).to_string(),
                 actual: claimed_eval.to_string(),
             });
@@ -192,6 +317,24 @@ where
         Ok(folding_randomness)
     }
 
+    /// Checks that embedded claims are well formed and match the configured number of variables.
+    fn check_claims(&self, claims: &InitialClaims<EF>) -> Result<(), VerifierError> {
+        if claims.num_variables != self.num_variables {
+            return Err(VerifierError::ClaimsMismatch {
+                details: format!(
+                    "claims are over {} variables, expected {}",
+                    claims.num_variables, self.num_variables
+                ),
+            });
+        }
+        if !claims.is_well_formed() {
+            return Err(VerifierError::ClaimsMismatch {
+                details: "claims have malformed points or evaluations".to_string(),
+            });
+        }
+        Ok(())
+    }
+
     /// Verify STIR in-domain queries and produce associated constraints.
     ///
     /// This method runs the STIR query phase on a given commitment.
@@ -206,7 +349,7 @@ where
     /// - `proof`: The WHIR proof containing query openings and Merkle proofs.
     /// - `challenger`: The Fiat-Shamir challenger for transcript management.
     /// - `params`: Parameters for the current STIR round (domain size, folding factor, etc.).
-    /// - `commitment`: The prover's commitment to the folded polynomial.
+    /// - `root`: The root of the prover's commitment to the folded polynomial.
     /// - `folding_randomness`: Random point for folding the evaluations.
     /// - `round_index`: The current round index in the protocol.
     ///
@@ -222,10 +365,38 @@ where
         proof: &WhirProof<F, EF, MT>,
         challenger: &mut Challenger,
         params: &RoundConfig<F>,
-        commitment: &ParsedCommitment<EF, MT::Commitment>,
+        root: StirRoot<'_, MT::Commitment, RoundCommitment<F, MT>>,
         folding_randomness: &Point<EF>,
         round_index: usize,
     ) -> Result<SelectStatement<F, EF>, VerifierError> {
+        let (stir_constraints, folds) = self.stir_answers(
+            proof,
+            challenger,
+            params,
+            root,
+            folding_randomness,
+            round_index,
+        )?;
+
+        Ok(SelectStatement::new(
+            params.num_variables,
+            stir_constraints,
+            folds,
+        ))
+    }
+
+    /// Runs the STIR query phase and returns the queried domain points with their folded values.
+    ///
+    /// This is [`Self::verify_stir_challenges`] before the answers are packaged as a statement.
+    fn stir_answers(
+        &self,
+        proof: &WhirProof<F, EF, MT>,
+        challenger: &mut Challenger,
+        params: &RoundConfig<F>,
+        root: StirRoot<'_, MT::Commitment, RoundCommitment<F, MT>>,
+        folding_randomness: &Point<EF>,
+        round_index: usize,
+    ) -> Result<(Vec<F>, Vec<EF>), VerifierError> {
         // CRITICAL: Verify the prover's proof-of-work before generating challenges.
         //
         // This is the verifier's counterpart to the prover's grinding step and is essential
@@ -241,15 +412,19 @@ where
         // commitment at a significant computational cost. This gives us confidence that the
         // challenges we generate are unpredictable and unbiased by a cheating prover.
         let pow_witness = if round_index < self.n_rounds() {
-            proof
-                .get_pow_after_commitment(round_index)
-                .ok_or(VerifierError::InvalidRoundIndex { index: round_index })?
+            proof.get_pow_after_commitment(round_index).ok_or_else(|| {
+                VerifierError::MalformedProof {
+                    round: round_index,
+                    phase: VerifierPhase::Pow,
+                    details: "round not found in proof".to_string(),
+                }
+            })?
         } else {
             // Final round uses final_pow_witness
             proof.final_pow_witness
         };
         if params.pow_bits > 0 && !challenger.check_witness(params.pow_bits, pow_witness) {
-            return Err(VerifierError::InvalidPowWitness);
+            return Err(VerifierError::InvalidPowWitness { round: round_index });
         }
 
         // Transcript checkpoint after PoW
@@ -262,7 +437,12 @@ where
             params.folding_factor,
             params.num_queries,
             challenger,
-        )?;
+        )
+        .map_err(|source| VerifierError::FiatShamir {
+            round: round_index,
+            phase: VerifierPhase::Stir,
+            source,
+        })?;
 
         let dimensions = vec![Dimensions {
             height: params.domain_size >> params.folding_factor,
@@ -270,15 +450,15 @@ where
         }];
         let answers = self.verify_merkle_proof(
             proof,
-            &commitment.root,
+            root,
             &stir_challenges_indexes,
             &dimensions,
             round_index,
         )?;
 
-        // Compute STIR Constraints
+        // Compute STIR Constraints, folding the answers in parallel
         let folds: Vec<_> = answers
-            .into_iter()
+            .into_par_iter()
             .map(|answer| Poly::new(answer).eval_ext::<F>(folding_randomness))
             .collect();
 
@@ -287,11 +467,7 @@ where
             .map(|&index| params.folded_domain_gen.exp_u64(index as u64))
             .collect();
 
-        Ok(SelectStatement::new(
-            params.num_variables,
-            stir_constraints,
-            folds,
-        ))
+        Ok((stir_constraints, folds))
     }
 
     /// Verify a Merkle multi-opening proof for the provided indices.
@@ -307,7 +483,8 @@ where
     ///
     /// # Arguments
     /// - `proof`: The WHIR proof containing query openings and Merkle proofs.
-    /// - `root`: The Merkle root hash the prover's claims are verified against.
+    /// - `root`: The Merkle root the prover's claims are verified against. Base field openings
+    ///   must be of the initial commitment, extension field openings of a round commitment.
     /// - `indices`: The list of queried leaf indices.
     /// - `dimensions`: The shape of the underlying matrix being committed (for MMCS verification).
     /// - `round_index`: The current round index to determine which queries to use from the proof.
@@ -316,16 +493,17 @@ where
     /// A vector of decoded leaf values, one `Vec<EF>` per queried index.
     ///
     /// # Errors
-    /// Returns `VerifierError::MerkleProofInvalid` if any Merkle proof fails verification.
+    /// Returns `VerifierError::MerkleProofInvalid` if any Merkle proof fails verification,
+    /// and `VerifierError::MalformedProof` if the proof lacks openings for the round.
     pub fn verify_merkle_proof(
         &self,
         proof: &WhirProof<F, EF, MT>,
-        root: &MT::Commitment,
+        root: StirRoot<'_, MT::Commitment, RoundCommitment<F, MT>>,
         indices: &[usize],
         dimensions: &[Dimensions],
         round_index: usize,
     ) -> Result<Vec<Vec<EF>>, VerifierError> {
-        let extension_mmcs = ExtensionMmcs::new(self.mmcs.clone());
+        let extension_mmcs = ExtensionMmcs::new(self.mmcs.round_mmcs().clone());
 
         // Determine which queries to use from the proof structure
         let queries = if round_index == self.n_rounds() {
@@ -334,39 +512,48 @@ where
             &proof
                 .rounds
                 .get(round_index)
-                .ok_or_else(|| VerifierError::MerkleProofInvalid {
-                    position: 0,
-                    reason: format!("Round {round_index} not found in proof"),
+                .ok_or_else(|| VerifierError::MalformedProof {
+                    round: round_index,
+                    phase: VerifierPhase::Stir,
+                    details: "round not found in proof".to_string(),
                 })?
                 .queries
         };
+        if queries.len() != indices.len() {
+            return Err(VerifierError::MalformedProof {
+                round: round_index,
+                phase: VerifierPhase::Stir,
+                details: format!(
+                    "expected {} query openings, got {}",
+                    indices.len(),
+                    queries.len()
+                ),
+            });
+        }
 
-        let mut results = Vec::with_capacity(indices.len());
-
-        for (&index, query) in indices.iter().zip(queries.iter()) {
-            let values_ef = match query {
-                QueryOpening::Base { values, proof } => {
-                    self.mmcs
-                        .verify_batch(
-                            root,
-                            dimensions,
-                            index,
-                            BatchOpeningRef {
-                                opened_values: from_ref(values),
-                                opening_proof: proof,
-                            },
-                        )
-                        .map_err(|_| VerifierError::MerkleProofInvalid {
-                            position: index,
-                            reason: "Base field Merkle proof verification failed".to_string(),
-                        })?;
-
-                    // Convert F -> EF
-                    values.iter().map(|&f| f.into()).collect()
+        // Openings are checked in parallel. The results are collected in query order before
+        // looking for a failure, so the error reported is always that of the first bad query.
+        let mmcs = &self.mmcs;
+        let results: Vec<_> = indices
+            .par_iter()
+            .zip(queries.par_iter())
+            .enumerate()
+            .map(|(query_index, (&index, query))| {
+                let width = match query {
+                    QueryOpening::Base { values, .. } => values.len(),
+                    QueryOpening::Extension { values, .. } => values.len(),
+                };
+                if dimensions.iter().any(|dims| dims.width != width) {
+                    return Err(VerifierError::MalformedProof {
+                        round: round_index,
+                        phase: VerifierPhase::Stir,
+                        details: format!("query {query_index} opens {width} values"),
+                    });
                 }
-                QueryOpening::Extension { values, proof } => {
-                    extension_mmcs
-                        .verify_batch(
+
+                match (query, root) {
+                    (QueryOpening::Base { values, proof }, StirRoot::Initial(root)) => {
+                        mmcs.verify_batch(
                             root,
                             dimensions,
                             index,
@@ -375,19 +562,63 @@ where
                                 opening_proof: proof,
                             },
                         )
-                        .map_err(|_| VerifierError::MerkleProofInvalid {
-                            position: index,
-                            reason: "Extension field Merkle proof verification failed".to_string(),
+                        .map_err(|_| {
+                            VerifierError::MerkleProofInvalid {
+                                round: round_index,
+                                query: query_index,
+                                position: index,
+                                reason: "Base field Merkle proof verification failed".to_string(),
+                            }
                         })?;
 
-                    values.clone()
+                        // Convert F -> EF
+                        Ok(values.iter().map(|&f| f.into()).collect())
+                    }
+                    (QueryOpening::Extension { values, proof }, StirRoot::Round(root)) => {
+                        extension_mmcs
+                            .verify_batch(
+                                root,
+                                dimensions,
+                                index,
+                                BatchOpeningRef {
+                                    opened_values: from_ref(values),
+                                    opening_proof: proof,
+                                },
+                            )
+                            .map_err(|_| VerifierError::MerkleProofInvalid {
+                                round: round_index,
+                                query: query_index,
+                                position: index,
+                                reason: "Extension field Merkle proof verification failed"
+                                    .to_string(),
+                            })?;
+
+                        Ok(values.clone())
+                    }
+                    (QueryOpening::Base { .. }, StirRoot::Round(_)) => {
+                        Err(VerifierError::MalformedProof {
+                            round: round_index,
+                            phase: VerifierPhase::Stir,
+                            details: format!(
+                                "query {query_index} opens base field values of a round commitment"
+                            ),
+                        })
+                    }
+                    (QueryOpening::Extension { .. }, StirRoot::Initial(_)) => {
+                        Err(VerifierError::MalformedProof {
+                            round: round_index,
+                            phase: VerifierPhase::Stir,
+                            details: format!(
+                                "query {query_index} opens extension field values of the initial \
+                                 commitment"
+                            ),
+                        })
+                    }
                 }
-            };
-
-            results.push(values_ef);
-        }
+            })
+            .collect();
 
-        Ok(results)
+        results.into_iter().collect()
     }
 }
 
@@ -0,0 +1,472 @@
+//! Malicious-prover tests: every component of a valid proof is tampered with in turn,
+//! and the verifier must reject each mutation.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::DuplexChallenger;
+use p3_dft::Radix2DFTSmallBatch;
+use p3_field::{Field, PrimeCharacteristicRing, extension::BinomialExtensionField};
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_multilinear_util::{point::Point, poly::Poly};
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use rand::{RngExt, SeedableRng, rngs::SmallRng};
+
+use super::{
+    Verifier,
+    errors::{VerifierError, VerifierPhase},
+};
+use crate::{
+    constraints::statement::EqStatement,
+    fiat_shamir::domain_separator::DomainSeparator,
+    parameters::{
+        FoldingFactor, ProtocolParameters, SecurityAssumption, SumcheckStrategy, WhirConfig,
+    },
+    whir::{
+        committer::{reader::CommitmentReader, writer::CommitmentWriter},
+        config::domain_separator,
+        proof::{QueryOpening, WhirProof},
+        prover::Prover,
+    },
+};
+
+type F = BabyBear;
+type EF = BinomialExtensionField<F, 4>;
+type Perm = Poseidon2BabyBear<16>;
+
+type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+type MyChallenger = DuplexChallenger<F, Perm, 16, 8>;
+
+type PackedF = <F as Field>::Packing;
+type MyMmcs = MerkleTreeMmcs<PackedF, PackedF, MyHash, MyCompress, 2, 8>;
+
+type Proof = WhirProof<F, EF, MyMmcs>;
+
+/// Number of PoW bits forced on every grinding step.
+///
+/// Large enough that a tampered witness is valid only with probability 2^-10.
+const POW_BITS: usize = 10;
+
+/// A valid proof together with everything needed to verify it.
+struct Harness {
+    params: WhirConfig<EF, F, MyMmcs, MyChallenger>,
+    challenger: MyChallenger,
+    domainsep: DomainSeparator<EF, F>,
+    statement: EqStatement<EF>,
+    proof: Proof,
+}
+
+impl Harness {
+    /// Produces a valid proof with several rounds, a final sumcheck and PoW in every phase.
+    fn new() -> Self {
+        let num_variables = 10;
+
+        let mut rng = SmallRng::seed_from_u64(1);
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let mmcs = MyMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm), 0);
+
+        let whir_params = ProtocolParameters {
+            security_level: 32,
+            pow_bits: 0,
+            rs_domain_initial_reduction_factor: 1,
+            folding_factor: FoldingFactor::Constant(2),
+            mmcs,
+            soundness_type: SecurityAssumption::CapacityBound,
+            starting_log_inv_rate: 1,
+        };
+        let mut params =
+            WhirConfig::<EF, F, MyMmcs, MyChallenger>::new(num_variables, whir_params.clone());
+
+        // Force grinding everywhere so that every PoW witness is checked.
+        params.starting_folding_pow_bits = POW_BITS;
+        for round in &mut params.round_parameters {
+            round.pow_bits = POW_BITS;
+            round.folding_pow_bits = POW_BITS;
+        }
+        params.final_pow_bits = POW_BITS;
+        params.final_folding_pow_bits = POW_BITS;
+
+        let polynomial = Poly::new((0..1 << num_variables).map(|_| rng.random()).collect());
+        let mut initial_statement = params.initial_statement(polynomial, SumcheckStrategy::Classic);
+        for _ in 0..2 {
+            let _ = initial_statement.evaluate(&Point::rand(&mut rng, num_variables));
+        }
+        let statement = initial_statement.normalize();
+
+        let mut domainsep = domain_separator(&params);
+        domainsep.commit_statement::<_, _, 8>(&params);
+        domainsep.add_whir_proof::<_, _, 8>(&params);
+        let challenger = MyChallenger::new(Perm::new_from_rng_128(&mut rng));
+
+        let dft = Radix2DFTSmallBatch::<F>::default();
+        let (root, prover_data) = CommitmentWriter::new(&params)
+            .commit(&dft, &initial_statement.poly)
+            .unwrap();
+        let mut proof = Proof::from_protocol_parameters(&whir_params, num_variables);
+        let mut prover_challenger = challenger.clone();
+        domainsep.observe_domain_separator(&mut prover_challenger);
+        Prover(&params)
+            .prove(
+                &dft,
+                &mut proof,
+                &mut prover_challenger,
+                &mut initial_statement,
+                root,
+                &prover_data,
+            )
+            .unwrap();
+
+        Self {
+            params,
+            challenger,
+            domainsep,
+            statement,
+            proof,
+        }
+    }
+
+    fn n_rounds(&self) -> usize {
+        self.params.n_rounds()
+    }
+
+    fn verify(&self, proof: &Proof) -> Result<Point<EF>, VerifierError> {
+        let mut challenger = self.challenger.clone();
+        self.domainsep.observe_domain_separator(&mut challenger);
+        let parsed_commitment =
+            CommitmentReader::new(&self.params).parse_commitment::<F, 8>(proof, &mut challenger)?;
+        Verifier::new(&self.params).verify(
+            proof,
+            &mut challenger,
+            &parsed_commitment,
+            self.statement.clone(),
+        )
+    }
+
+    /// Applies `tamper` to a copy of the valid proof and checks the rejection with `expected`.
+    fn assert_rejected(
+        &self,
+        what: &str,
+        tamper: impl FnOnce(&mut Proof),
+        expected: impl FnOnce(&VerifierError) -> bool,
+    ) {
+        let mut proof = self.proof.clone();
+        tamper(&mut proof);
+        match self.verify(&proof) {
+            Ok(_) => panic!("tampered {what} was accepted"),
+            Err(err) => assert!(
+                expected(&err),
+                "unexpected error for tampered {what}: {err}"
+            ),
+        }
+    }
+}
+
+fn tamper_leaf(query: &mut QueryOpening<F, EF, Vec<[F; 8]>>) {
+    match query {
+        QueryOpening::Base { values, .. } => values[0] += F::ONE,
+        QueryOpening::Extension { values, .. } => values[0] += EF::ONE,
+    }
+}
+
+fn tamper_sibling(query: &mut QueryOpening<F, EF, Vec<[F; 8]>>) {
+    match query {
+        QueryOpening::Base { proof, .. } | QueryOpening::Extension { proof, .. } => {
+            proof[0][0] += F::ONE;
+        }
+    }
+}
+
+fn is_merkle_failure(err: &VerifierError, expected_round: usize) -> bool {
+    matches!(
+        err,
+        VerifierError::MerkleProofInvalid { round, query: 0, .. } if *round == expected_round
+    )
+}
+
+/// Whether the STIR PoW witness of `expected_round` was rejected.
+///
+/// Data absorbed into the transcript changes every later challenge, including the ones
+/// the next PoW witness is checked against. With `POW_BITS` of grinding everywhere, that
+/// PoW is the first check to fail after tampering with such data.
+fn is_pow_failure(err: &VerifierError, expected_round: usize) -> bool {
+    matches!(err, VerifierError::InvalidPowWitness { round } if *round == expected_round)
+}
+
+/// Whether a sumcheck of `expected_phase` failed in `expected_round`, which is its PoW
+/// check when the tampered data is absorbed by that sumcheck or just before it.
+fn is_sumcheck_failure(
+    err: &VerifierError,
+    expected_round: usize,
+    expected_phase: VerifierPhase,
+) -> bool {
+    matches!(
+        err,
+        VerifierError::Sumcheck { round, phase, .. }
+            if *round == expected_round && *phase == expected_phase
+    )
+}
+
+#[test]
+fn test_valid_proof_is_accepted() {
+    let harness = Harness::new();
+    assert!(harness.n_rounds() >= 2);
+    assert!(harness.params.final_sumcheck_rounds > 0);
+    harness.verify(&harness.proof).unwrap();
+}
+
+#[test]
+fn test_tampered_commitments_are_rejected() {
+    let harness = Harness::new();
+
+    // Replace each round commitment with another valid-looking one.
+    for round in 0..harness.n_rounds() {
+        harness.assert_rejected(
+            &format!("commitment of round {round}"),
+            |proof| proof.rounds[round].commitment = proof.initial_commitment.clone(),
+            |err| is_pow_failure(err, round),
+        );
+    }
+
+    // The initial commitment is absorbed before the initial sumcheck.
+    harness.assert_rejected(
+        "initial commitment",
+        |proof| proof.initial_commitment = proof.rounds[0].commitment.clone(),
+        |err| is_sumcheck_failure(err, 0, VerifierPhase::InitialSumcheck),
+    );
+}
+
+#[test]
+fn test_tampered_ood_answers_are_rejected() {
+    let harness = Harness::new();
+
+    harness.assert_rejected(
+        "initial OOD answer",
+        |proof| proof.initial_ood_answers[0] += EF::ONE,
+        |err| is_sumcheck_failure(err, 0, VerifierPhase::InitialSumcheck),
+    );
+    for round in 0..harness.n_rounds() {
+        harness.assert_rejected(
+            &format!("OOD answer of round {round}"),
+            |proof| proof.rounds[round].ood_answers[0] += EF::ONE,
+            |err| is_pow_failure(err, round),
+        );
+    }
+
+    // Missing answers are reported as a malformed proof.
+    harness.assert_rejected(
+        "OOD answers count",
+        |proof| {
+            proof.rounds[0].ood_answers.pop();
+        },
+        |err| {
+            matches!(
+                err,
+                VerifierError::MalformedProof {
+                    round: 0,
+                    phase: VerifierPhase::Ood,
+                    ..
+                }
+            )
+        },
+    );
+}
+
+#[test]
+fn test_tampered_sumcheck_coefficients_are_rejected() {
+    let harness = Harness::new();
+
+    harness.assert_rejected(
+        "initial sumcheck coefficients",
+        |proof| proof.initial_sumcheck.polynomial_evaluations.swap(0, 1),
+        |err| is_sumcheck_failure(err, 0, VerifierPhase::InitialSumcheck),
+    );
+    for round in 0..harness.n_rounds() {
+        harness.assert_rejected(
+            &format!("sumcheck coefficients of round {round}"),
+            |proof| {
+                proof.rounds[round]
+                    .sumcheck
+                    .polynomial_evaluations
+                    .swap(0, 1);
+            },
+            |err| is_sumcheck_failure(err, round, VerifierPhase::RoundSumcheck),
+        );
+    }
+}
+
+#[test]
+fn test_tampered_pow_witnesses_are_rejected() {
+    let harness = Harness::new();
+    let n_rounds = harness.n_rounds();
+
+    // Grinding after each round commitment
+    for round in 0..n_rounds {
+        harness.assert_rejected(
+            &format!("PoW witness of round {round}"),
+            |proof| proof.rounds[round].pow_witness += F::ONE,
+            |err| is_pow_failure(err, round),
+        );
+    }
+    harness.assert_rejected(
+        "final PoW witness",
+        |proof| proof.final_pow_witness += F::ONE,
+        |err| is_pow_failure(err, n_rounds),
+    );
+
+    // Grinding inside the sumchecks
+    harness.assert_rejected(
+        "initial sumcheck PoW witness",
+        |proof| proof.initial_sumcheck.pow_witnesses[0] += F::ONE,
+        |err| {
+            matches!(
+                err,
+                VerifierError::Sumcheck {
+                    round: 0,
+                    phase: VerifierPhase::InitialSumcheck,
+                    ..
+                }
+            )
+        },
+    );
+    for round in 0..n_rounds {
+        harness.assert_rejected(
+            &format!("sumcheck PoW witness of round {round}"),
+            |proof| proof.rounds[round].sumcheck.pow_witnesses[0] += F::ONE,
+            |err| {
+                matches!(
+                    err,
+                    VerifierError::Sumcheck {
+                        round: r,
+                        phase: VerifierPhase::RoundSumcheck,
+                        ..
+                    } if *r == round
+                )
+            },
+        );
+    }
+    harness.assert_rejected(
+        "final sumcheck PoW witness",
+        |proof| proof.final_sumcheck.as_mut().unwrap().pow_witnesses[0] += F::ONE,
+        |err| {
+            matches!(
+                err,
+                VerifierError::Sumcheck {
+                    round,
+                    phase: VerifierPhase::FinalSumcheck,
+                    ..
+                } if *round == n_rounds
+            )
+        },
+    );
+}
+
+#[test]
+fn test_tampered_openings_are_rejected() {
+    let harness = Harness::new();
+    let n_rounds = harness.n_rounds();
+
+    // Leaf values and Merkle siblings are not absorbed into the transcript, so the
+    // failing query is known exactly.
+    for round in 0..n_rounds {
+        harness.assert_rejected(
+            &format!("leaf of round {round}"),
+            |proof| tamper_leaf(&mut proof.rounds[round].queries[0]),
+            |err| is_merkle_failure(err, round),
+        );
+        harness.assert_rejected(
+            &format!("Merkle sibling of round {round}"),
+            |proof| tamper_sibling(&mut proof.rounds[round].queries[0]),
+            |err| is_merkle_failure(err, round),
+        );
+    }
+    harness.assert_rejected(
+        "final leaf",
+        |proof| tamper_leaf(&mut proof.final_queries[0]),
+        |err| is_merkle_failure(err, n_rounds),
+    );
+    harness.assert_rejected(
+        "final Merkle sibling",
+        |proof| tamper_sibling(&mut proof.final_queries[0]),
+        |err| is_merkle_failure(err, n_rounds),
+    );
+
+    // Dropping an opening is reported as a malformed proof.
+    harness.assert_rejected(
+        "query count",
+        |proof| {
+            proof.rounds[0].queries.pop();
+        },
+        |err| {
+            matches!(
+                err,
+                VerifierError::MalformedProof {
+                    round: 0,
+                    phase: VerifierPhase::Stir,
+                    ..
+                }
+            )
+        },
+    );
+}
+
+#[test]
+fn test_tampered_final_phase_is_rejected() {
+    let harness = Harness::new();
+    let n_rounds = harness.n_rounds();
+
+    // The final polynomial is absorbed before the final PoW.
+    harness.assert_rejected(
+        "final polynomial",
+        |proof| {
+            let final_poly = proof.final_poly.as_mut().unwrap();
+            let mut evals = final_poly.as_slice().to_vec();
+            evals[0] += EF::ONE;
+            *final_poly = Poly::new(evals);
+        },
+        |err| is_pow_failure(err, n_rounds),
+    );
+
+    // The final sumcheck comes after every other check.
+    harness.assert_rejected(
+        "final sumcheck coefficients",
+        |proof| {
+            proof
+                .final_sumcheck
+                .as_mut()
+                .unwrap()
+                .polynomial_evaluations
+                .swap(0, 1);
+        },
+        |err| is_sumcheck_failure(err, n_rounds, VerifierPhase::FinalSumcheck),
+    );
+
+    harness.assert_rejected(
+        "missing final polynomial",
+        |proof| proof.final_poly = None,
+        |err| {
+            matches!(
+                err,
+                VerifierError::MalformedProof {
+                    phase: VerifierPhase::FinalPoly,
+                    ..
+                }
+            )
+        },
+    );
+}
+
+#[test]
+fn test_error_messages_locate_the_failure() {
+    let harness = Harness::new();
+    let mut proof = harness.proof.clone();
+    tamper_leaf(&mut proof.rounds[1].queries[0]);
+
+    let message: String = harness.verify(&proof).unwrap_err().to_string();
+    assert!(message.contains("round 1"), "{message}");
+    assert!(message.contains("query 0"), "{message}");
+}